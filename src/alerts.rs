@@ -0,0 +1,454 @@
+use crate::controller::Controller;
+use crate::duration;
+use crate::timer::{Timer, TimerEvent, TimerType};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+// replaced with the path of a temporary file for players that can't read from stdin
+const FILE_PLACEHOLDER: &str = "{file}";
+
+// numbers the temporary files of sounds, alerts of several timers can play at the same time
+static PLAYS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AlertKind {
+    WorkEnd,
+    BreakEnd,
+    LongBreakEnd,
+}
+
+impl AlertKind {
+    pub fn of(timer: &Timer) -> AlertKind {
+        match timer.timer_type() {
+            TimerType::Work => AlertKind::WorkEnd,
+            TimerType::Break => AlertKind::BreakEnd,
+            TimerType::LongBreak => AlertKind::LongBreakEnd,
+        }
+    }
+}
+
+#[derive(Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    // play sounds with an external player
+    Command,
+    // don't make any sound, e.g. on a headless machine
+    Null,
+}
+
+// the [alerts] table of the config file
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AlertConfig {
+    pub backend: Backend,
+    // ring the terminal bell when there's no sound for a timer
+    pub bell: bool,
+    // between 0.0 and 1.0
+    pub volume: f32,
+    // keep alerting until the next timer is started
+    pub repeat: bool,
+    // time between repeated alerts, a bare number is a number of seconds
+    #[serde(with = "duration::secs")]
+    pub repeat_interval: Duration,
+    // the player reads a WAV file from stdin, or from {file}
+    pub player: Vec<String>,
+    pub work_end: Option<PathBuf>,
+    pub break_end: Option<PathBuf>,
+    pub long_break_end: Option<PathBuf>,
+}
+
+impl Default for AlertConfig {
+    fn default() -> AlertConfig {
+        AlertConfig {
+            backend: Backend::Command,
+            bell: true,
+            volume: 1.0,
+            repeat: false,
+            repeat_interval: Duration::from_secs(30),
+            player: default_player(),
+            work_end: None,
+            break_end: None,
+            long_break_end: None,
+        }
+    }
+}
+
+impl AlertConfig {
+    fn sound_file(&self, kind: AlertKind) -> Option<&Path> {
+        match kind {
+            AlertKind::WorkEnd => self.work_end.as_deref(),
+            AlertKind::BreakEnd => self.break_end.as_deref(),
+            AlertKind::LongBreakEnd => self.long_break_end.as_deref(),
+        }
+    }
+}
+
+fn default_player() -> Vec<String> {
+    let player: &[&str] = if cfg!(target_os = "macos") {
+        &["afplay", FILE_PLACEHOLDER]
+    } else {
+        &["paplay"]
+    };
+
+    player.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[derive(Debug)]
+pub enum SoundError {
+    Read(PathBuf, io::Error),
+    Decode(PathBuf, String),
+    UnsupportedFormat(PathBuf),
+}
+
+impl fmt::Display for SoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoundError::Read(path, err) => {
+                write!(f, "Failed to read {}: {}", path.display(), err)
+            }
+            SoundError::Decode(path, err) => {
+                write!(f, "Failed to decode {}: {}", path.display(), err)
+            }
+            SoundError::UnsupportedFormat(path) => write!(
+                f,
+                "Unsupported sound file {}, use a .wav or .ogg file.",
+                path.display()
+            ),
+        }
+    }
+}
+
+// decoded audio as interleaved 16 bit samples
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sound {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+impl Sound {
+    pub fn load(path: &Path) -> Result<Sound, SoundError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        match extension.as_deref() {
+            Some("wav") => Sound::load_wav(path),
+            Some("ogg") => Sound::load_ogg(path),
+            _ => Err(SoundError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    fn load_wav(path: &Path) -> Result<Sound, SoundError> {
+        let decode_error = |err: hound::Error| match err {
+            hound::Error::IoError(err) => SoundError::Read(path.to_path_buf(), err),
+            err => SoundError::Decode(path.to_path_buf(), err.to_string()),
+        };
+
+        let reader = hound::WavReader::open(path).map_err(decode_error)?;
+        let spec = reader.spec();
+
+        // everything is converted to 16 bit
+        let samples = match spec.sample_format {
+            hound::SampleFormat::Int if spec.bits_per_sample <= 16 => {
+                reader.into_samples::<i16>().collect::<Result<Vec<_>, _>>()
+            }
+            hound::SampleFormat::Int => {
+                let shift = spec.bits_per_sample - 16;
+
+                reader
+                    .into_samples::<i32>()
+                    .map(|sample| sample.map(|sample| (sample >> shift) as i16))
+                    .collect()
+            }
+            hound::SampleFormat::Float => reader
+                .into_samples::<f32>()
+                .map(|sample| sample.map(|sample| (sample * i16::MAX as f32) as i16))
+                .collect(),
+        }
+        .map_err(decode_error)?;
+
+        Ok(Sound {
+            channels: spec.channels,
+            sample_rate: spec.sample_rate,
+            samples,
+        })
+    }
+
+    fn load_ogg(path: &Path) -> Result<Sound, SoundError> {
+        let decode_error =
+            |err: lewton::VorbisError| SoundError::Decode(path.to_path_buf(), err.to_string());
+
+        let file = File::open(path).map_err(|err| SoundError::Read(path.to_path_buf(), err))?;
+        let mut reader =
+            lewton::inside_ogg::OggStreamReader::new(BufReader::new(file)).map_err(decode_error)?;
+
+        let mut samples = Vec::new();
+
+        while let Some(packet) = reader.read_dec_packet_itl().map_err(decode_error)? {
+            samples.extend(packet);
+        }
+
+        Ok(Sound {
+            channels: reader.ident_hdr.audio_channels.into(),
+            sample_rate: reader.ident_hdr.audio_sample_rate,
+            samples,
+        })
+    }
+
+    pub fn with_volume(&self, volume: f32) -> Sound {
+        let volume = volume.clamp(0.0, 1.0);
+
+        Sound {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            samples: self
+                .samples
+                .iter()
+                .map(|sample| (*sample as f32 * volume) as i16)
+                .collect(),
+        }
+    }
+
+    pub fn to_wav(&self) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut wav = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut wav, spec).expect("Failed to write WAV");
+
+        for sample in &self.samples {
+            writer.write_sample(*sample).expect("Failed to write WAV");
+        }
+
+        writer.finalize().expect("Failed to write WAV");
+
+        wav.into_inner()
+    }
+}
+
+pub trait AudioBackend: Send + Sync {
+    fn bell(&self);
+    fn play(&self, sound: &Sound) -> io::Result<()>;
+}
+
+// rings the bell of the terminal pomo runs in and plays sounds with an external player, pomo decodes
+// them itself and hands the player a WAV file
+pub struct CommandBackend {
+    command: Vec<String>,
+}
+
+impl CommandBackend {
+    pub fn new(command: Vec<String>) -> CommandBackend {
+        CommandBackend { command }
+    }
+}
+
+impl AudioBackend for CommandBackend {
+    fn bell(&self) {
+        // there's no terminal when running in the background
+        if let Ok(mut tty) = OpenOptions::new().write(true).open("/dev/tty") {
+            let _ = tty.write_all(b"\x07");
+        }
+    }
+
+    fn play(&self, sound: &Sound) -> io::Result<()> {
+        let Some((program, args)) = self.command.split_first() else {
+            return Ok(());
+        };
+
+        let wav = sound.to_wav();
+
+        if !args.iter().any(|arg| arg == FILE_PLACEHOLDER) {
+            let mut child = std::process::Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .spawn()?;
+
+            // a player that exits without reading all of it still has to be waited for
+            let written = match child.stdin.take() {
+                Some(mut stdin) => stdin.write_all(&wav),
+                None => Ok(()),
+            };
+
+            child.wait()?;
+            return written;
+        }
+
+        let path = std::env::temp_dir().join(format!(
+            "pomo-{}-{}.wav",
+            std::process::id(),
+            PLAYS.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        // never write through a file someone else put there
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        file.write_all(&wav)?;
+        drop(file);
+
+        let args = args.iter().map(|arg| {
+            if arg == FILE_PLACEHOLDER {
+                path.display().to_string()
+            } else {
+                arg.clone()
+            }
+        });
+
+        let status = std::process::Command::new(program).args(args).status();
+        std::fs::remove_file(&path).unwrap_or(());
+
+        status.map(|_| ())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Played {
+    Bell,
+    Sound(Sound),
+}
+
+// plays nothing, but remembers what it was asked to play
+#[derive(Default)]
+pub struct NullBackend {
+    played: Mutex<Vec<Played>>,
+}
+
+impl NullBackend {
+    pub fn played(&self) -> Vec<Played> {
+        self.played.lock().expect("Failed to lock played").clone()
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn bell(&self) {
+        let mut played = self.played.lock().expect("Failed to lock played");
+        played.push(Played::Bell);
+    }
+
+    fn play(&self, sound: &Sound) -> io::Result<()> {
+        let mut played = self.played.lock().expect("Failed to lock played");
+        played.push(Played::Sound(sound.clone()));
+        Ok(())
+    }
+}
+
+// plays an alert whenever a timer finishes
+pub struct Alerter {
+    config: AlertConfig,
+    sounds: HashMap<AlertKind, Arc<Sound>>,
+    backend: Arc<dyn AudioBackend>,
+}
+
+impl Alerter {
+    // sounds are loaded up front, so a broken file is reported when pomo starts
+    pub fn new(config: AlertConfig, backend: Arc<dyn AudioBackend>) -> Result<Alerter, SoundError> {
+        let mut sounds = HashMap::new();
+
+        for kind in [
+            AlertKind::WorkEnd,
+            AlertKind::BreakEnd,
+            AlertKind::LongBreakEnd,
+        ] {
+            if let Some(path) = config.sound_file(kind) {
+                let sound = Sound::load(path)?.with_volume(config.volume);
+                sounds.insert(kind, Arc::new(sound));
+            }
+        }
+
+        Ok(Alerter {
+            config,
+            sounds,
+            backend,
+        })
+    }
+
+    pub fn alert(&self, kind: AlertKind) {
+        match self.sounds.get(&kind) {
+            Some(sound) => {
+                let sound = Arc::clone(sound);
+                let backend = Arc::clone(&self.backend);
+
+                // players take a while, don't hold up the timer
+                std::thread::spawn(move || {
+                    if let Err(err) = backend.play(&sound) {
+                        eprintln!("Failed to play sound: {}", err);
+                    }
+                });
+            }
+            None if self.config.bell => self.backend.bell(),
+            None => {}
+        }
+    }
+
+    pub fn watch(self: &Arc<Self>, controller: &Arc<Mutex<Controller>>) {
+        let alerter = Arc::clone(self);
+        let auto = Controller::config(controller).auto;
+        let controller_ref = Arc::downgrade(controller);
+
+        Controller::on(
+            controller,
+            TimerEvent::Finish,
+            Arc::new(move |timer: &Timer| {
+                let kind = AlertKind::of(timer);
+
+                alerter.alert(kind);
+
+                // in auto mode the next timer has started already, nothing to acknowledge
+                if !alerter.config.repeat || auto {
+                    return;
+                }
+
+                let alerter = Arc::clone(&alerter);
+                let controller = controller_ref.clone();
+
+                tokio::spawn(async move {
+                    alerter.repeat(kind, controller).await;
+                });
+            }),
+        );
+    }
+
+    // alert again until the finished timer is left behind, e.g. by starting the next one
+    async fn repeat(&self, kind: AlertKind, controller: Weak<Mutex<Controller>>) {
+        let timer = match controller.upgrade() {
+            Some(controller) => Controller::get_current_timer(&controller),
+            None => return,
+        };
+
+        let interval = self.config.repeat_interval.max(Duration::from_secs(1));
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let Some(controller) = controller.upgrade() else {
+                return;
+            };
+
+            let current_timer = Controller::get_current_timer(&controller);
+
+            let finished = timer.lock().expect("Failed to lock timer").is_finished();
+
+            if !Arc::ptr_eq(&current_timer, &timer) || !finished {
+                return;
+            }
+
+            self.alert(kind);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::protocol::{self, Command, Event, Outcome, Reply, Request, Response};
+use std::fmt;
+use std::io::{self, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+// a connection to a running pomo, which can carry any number of requests
+pub struct Client {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+    session: Option<String>,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Io(io::Error),
+    Closed,
+    InvalidResponse,
+    Daemon(protocol::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(err) => write!(f, "Failed to talk to pomo: {}", err),
+            ClientError::Closed => write!(f, "Pomo closed the connection without replying."),
+            ClientError::InvalidResponse => write!(
+                f,
+                "Pomo sent an unexpected reply. Is it running an older version?"
+            ),
+            ClientError::Daemon(err) => write!(f, "Error: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> ClientError {
+        ClientError::Io(err)
+    }
+}
+
+impl Client {
+    pub fn connect(path: &Path) -> io::Result<Client> {
+        let stream = UnixStream::connect(path)?;
+        let reader = BufReader::new(stream.try_clone()?);
+
+        Ok(Client {
+            stream,
+            reader,
+            session: None,
+            next_id: 1,
+        })
+    }
+
+    // send all further requests to the named session
+    pub fn with_session(mut self, session: Option<String>) -> Client {
+        self.session = session;
+        self
+    }
+
+    pub fn request(&mut self, command: Command) -> Result<Reply, ClientError> {
+        let request = Request::new(self.next_id, self.session.clone(), command);
+        self.next_id += 1;
+
+        protocol::write_message(&mut self.stream, &request)?;
+
+        loop {
+            let line = protocol::read_message(&mut self.reader)?.ok_or(ClientError::Closed)?;
+
+            // events are only sent after subscribing, but don't mistake one for a reply
+            if serde_json::from_str::<Event>(&line).is_ok() {
+                continue;
+            }
+
+            let response: Response =
+                serde_json::from_str(&line).map_err(|_| ClientError::InvalidResponse)?;
+
+            // skip anything that isn't the reply to our request
+            if response.id.is_some() && response.id != Some(request.id) {
+                continue;
+            }
+
+            return match response.outcome {
+                Outcome::Result(reply) => Ok(reply),
+                Outcome::Error(error) => Err(ClientError::Daemon(error)),
+            };
+        }
+    }
+
+    // turn the connection into a stream of events of the session, or of all sessions if `all` is
+    // set, with one tick per second if `ticks` is set
+    pub fn subscribe(mut self, ticks: bool, all: bool) -> Result<Subscription, ClientError> {
+        self.request(Command::Subscribe { ticks, all })?;
+
+        Ok(Subscription {
+            _stream: self.stream,
+            reader: self.reader,
+        })
+    }
+}
+
+pub struct Subscription {
+    _stream: UnixStream,
+    reader: BufReader<UnixStream>,
+}
+
+// ends when pomo closes the connection
+impl Iterator for Subscription {
+    type Item = Result<Event, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match protocol::read_message(&mut self.reader) {
+            Ok(Some(line)) => line,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err.into())),
+        };
+
+        Some(serde_json::from_str(&line).map_err(|_| ClientError::InvalidResponse))
+    }
+}
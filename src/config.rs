@@ -0,0 +1,243 @@
+use crate::alerts::AlertConfig;
+use crate::controller::{Config, Kind};
+use crate::duration;
+use crate::hooks::HookConfig;
+use crate::paths;
+use crate::sequence::Sequence;
+use crate::suspend::SuspendPolicy;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// A partial configuration. Each layer (file, profile, environment, command line) only sets the
+// values it knows about and is applied on top of the previous one.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    #[serde(deserialize_with = "duration::deserialize_minutes")]
+    pub work_duration: Option<Duration>,
+    #[serde(deserialize_with = "duration::deserialize_minutes")]
+    pub break_duration: Option<Duration>,
+    pub long_break_interval: Option<u64>,
+    #[serde(deserialize_with = "duration::deserialize_minutes")]
+    pub long_break_duration: Option<Duration>,
+    pub count_skipped: Option<bool>,
+    pub suspend: Option<SuspendPolicy>,
+    pub auto: Option<bool>,
+    pub notifications: Option<bool>,
+    pub sequence: Option<Sequence>,
+    // a profile adds to the kinds of the config file
+    pub kinds: BTreeMap<String, Kind>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct ConfigFile {
+    #[serde(flatten)]
+    pub settings: Settings,
+    #[serde(default)]
+    pub profiles: HashMap<String, Settings>,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    #[serde(default)]
+    pub hooks: HookConfig,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, String),
+    UnknownProfile(String, Vec<String>),
+    InvalidEnv(String, String),
+    InvalidDuration(String, String),
+    UnknownKind(String, Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => {
+                write!(f, "Failed to read {}: {}", path.display(), err)
+            }
+            ConfigError::Parse(path, err) => {
+                write!(f, "Failed to parse {}: {}", path.display(), err)
+            }
+            ConfigError::UnknownProfile(name, available) if available.is_empty() => {
+                write!(f, "Unknown profile '{}', no profiles are configured.", name)
+            }
+            ConfigError::UnknownProfile(name, available) => write!(
+                f,
+                "Unknown profile '{}', available profiles: {}.",
+                name,
+                available.join(", ")
+            ),
+            ConfigError::InvalidDuration(name, value) => write!(
+                f,
+                "Invalid duration '{}' for {}, use for example {}.",
+                value,
+                name,
+                duration::EXAMPLES
+            ),
+            ConfigError::UnknownKind(name, available) if available.is_empty() => write!(
+                f,
+                "Unknown kind '{}' in the sequence, use work, break, long or define it in a \
+                 [kinds.{}] table.",
+                name, name
+            ),
+            ConfigError::UnknownKind(name, available) => write!(
+                f,
+                "Unknown kind '{}' in the sequence, use work, break, long or one of: {}.",
+                name,
+                available.join(", ")
+            ),
+            ConfigError::InvalidEnv(name, value) => {
+                write!(f, "Invalid value '{}' for {}.", value, name)
+            }
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_env() -> Result<Settings, ConfigError> {
+        Ok(Settings {
+            work_duration: env_duration("POMO_WORK_DURATION")?,
+            break_duration: env_duration("POMO_BREAK_DURATION")?,
+            long_break_interval: env_var("POMO_LONG_BREAK_INTERVAL")?,
+            long_break_duration: env_duration("POMO_LONG_BREAK_DURATION")?,
+            count_skipped: env_var("POMO_COUNT_SKIPPED")?,
+            suspend: env_var("POMO_SUSPEND")?,
+            auto: env_var("POMO_AUTO")?,
+            notifications: env_var("POMO_NOTIFICATIONS")?,
+            sequence: env_var("POMO_SEQUENCE")?,
+            kinds: BTreeMap::new(),
+        })
+    }
+
+    // values set in `other` take precedence
+    pub fn merge(self, other: Settings) -> Settings {
+        let mut kinds = self.kinds;
+        kinds.extend(other.kinds);
+
+        Settings {
+            work_duration: other.work_duration.or(self.work_duration),
+            break_duration: other.break_duration.or(self.break_duration),
+            long_break_interval: other.long_break_interval.or(self.long_break_interval),
+            long_break_duration: other.long_break_duration.or(self.long_break_duration),
+            count_skipped: other.count_skipped.or(self.count_skipped),
+            suspend: other.suspend.or(self.suspend),
+            auto: other.auto.or(self.auto),
+            notifications: other.notifications.or(self.notifications),
+            sequence: other.sequence.or(self.sequence),
+            kinds,
+        }
+    }
+
+    pub fn apply(&self, config: Config) -> Config {
+        let mut kinds = config.kinds;
+        kinds.extend(self.kinds.clone());
+
+        Config {
+            work_duration: self.work_duration.unwrap_or(config.work_duration),
+            break_duration: self.break_duration.unwrap_or(config.break_duration),
+            long_break_interval: self
+                .long_break_interval
+                .unwrap_or(config.long_break_interval),
+            long_break_duration: self
+                .long_break_duration
+                .unwrap_or(config.long_break_duration),
+            count_skipped: self.count_skipped.unwrap_or(config.count_skipped),
+            suspend: self.suspend.unwrap_or(config.suspend),
+            auto: self.auto.unwrap_or(config.auto),
+            notifications: self.notifications.unwrap_or(config.notifications),
+            sequence: self.sequence.clone().or(config.sequence),
+            kinds,
+        }
+    }
+}
+
+impl ConfigFile {
+    // a missing file is the same as an empty one
+    pub fn load(path: &Path) -> Result<ConfigFile, ConfigError> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(ConfigFile::default())
+            }
+            Err(err) => return Err(ConfigError::Read(path.to_path_buf(), err)),
+        };
+
+        toml::from_str(&contents)
+            .map_err(|err| ConfigError::Parse(path.to_path_buf(), err.to_string()))
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Settings, ConfigError> {
+        self.profiles.get(name).ok_or_else(|| {
+            let mut available: Vec<String> = self.profiles.keys().cloned().collect();
+            available.sort();
+
+            ConfigError::UnknownProfile(name.to_string(), available)
+        })
+    }
+
+    // the kinds of the config file and of all its profiles
+    pub fn kinds(&self) -> BTreeMap<String, Kind> {
+        let mut kinds = self.settings.kinds.clone();
+
+        for profile in self.profiles.values() {
+            kinds.extend(profile.kinds.clone());
+        }
+
+        kinds
+    }
+}
+
+// Builds the controller config from, in increasing order of precedence, the defaults, the config
+// file, the selected profile, the environment and the command line.
+pub fn resolve(profile: Option<&str>, cli: Settings) -> Result<Config, ConfigError> {
+    let file = ConfigFile::load(&paths::config_file())?;
+
+    let mut settings = file.settings.clone();
+
+    let profile = match profile {
+        Some(profile) => Some(profile.to_string()),
+        None => env_var("POMO_PROFILE")?,
+    };
+
+    if let Some(profile) = profile {
+        settings = settings.merge(file.profile(&profile)?.clone());
+    }
+
+    let settings = settings.merge(Settings::from_env()?).merge(cli);
+
+    let config = settings.apply(Config::default());
+
+    if let Some(kind) = config.unknown_kind() {
+        return Err(ConfigError::UnknownKind(
+            kind.to_string(),
+            config.kinds.keys().cloned().collect(),
+        ));
+    }
+
+    Ok(config)
+}
+
+fn env_var<T: std::str::FromStr>(name: &str) -> Result<Option<T>, ConfigError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidEnv(name.to_string(), value)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn env_duration(name: &str) -> Result<Option<Duration>, ConfigError> {
+    match std::env::var(name) {
+        Ok(value) => match duration::parse(&value) {
+            Ok(duration) => Ok(Some(duration)),
+            Err(_) => Err(ConfigError::InvalidDuration(name.to_string(), value)),
+        },
+        Err(_) => Ok(None),
+    }
+}
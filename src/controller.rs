@@ -1,18 +1,152 @@
-use crate::timer::{Timer, TimerEvent, TimerType};
-use flume;
-use std::collections::HashMap;
+use crate::duration;
+use crate::scheduler::{Ending, Scheduler, WorkCounts};
+use crate::sequence::{Sequence, Step};
+use crate::state::{self, SavedState};
+use crate::status::{SequenceStatus, Status};
+use crate::suspend::{SuspendDetector, SuspendPolicy};
+use crate::timer::{
+    Adjustment, EventHandler, Interruption, Timer, TimerEvent, TimerSnapshot, TimerState, TimerType,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
 use tokio::task;
 
-#[derive(Clone)]
+// how often the state of a running timer is saved in between transitions
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+// how often to look for the system having been suspended
+const SUSPEND_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// decides whether a new timer may start, and may change its duration or label. Returns why the
+// timer may not start.
+pub type StartGuard = Arc<dyn Fn(&mut Timer) -> Result<(), String> + Send + Sync>;
+
+// why `start` or `next` didn't start a timer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotStarted {
+    // a start guard refused, with its reason
+    Cancelled(String),
+    // the last step of a sequence that doesn't repeat is done
+    SequenceComplete,
+}
+
+impl fmt::Display for NotStarted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotStarted::Cancelled(reason) => write!(f, "{}", reason),
+            NotStarted::SequenceComplete => write!(
+                f,
+                "The sequence is complete, use 'pomo start' to go through it again."
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
+    #[serde(with = "duration::secs")]
     pub work_duration: Duration,
+    #[serde(with = "duration::secs")]
     pub break_duration: Duration,
+    #[serde(with = "duration::secs")]
     pub long_break_duration: Duration,
     pub long_break_interval: u64,
+    // whether skipped work timers count towards the long break
+    #[serde(default)]
+    pub count_skipped: bool,
+    // what a running timer does when the system was suspended
+    #[serde(default)]
+    pub suspend: SuspendPolicy,
     pub auto: bool,
+    #[serde(default = "enabled")]
+    pub notifications: bool,
+    // replaces the durations and long break interval above
+    #[serde(default)]
+    pub sequence: Option<Sequence>,
+    // kinds of timers besides work, break and long break that sequences can use
+    #[serde(default)]
+    pub kinds: BTreeMap<String, Kind>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+// a kind of timer such as a meeting, which runs and is counted like its timer type but has its own
+// hooks, status prefix and statistics
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Kind {
+    #[serde(rename = "type", default = "work")]
+    pub timer_type: TimerType,
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+fn work() -> TimerType {
+    TimerType::Work
+}
+
+impl Config {
+    // the timer type and kind of a sequence step, None if there is no such kind
+    pub fn resolve_kind(&self, name: &str) -> Option<(TimerType, Option<String>)> {
+        match name {
+            "work" => Some((TimerType::Work, None)),
+            "break" => Some((TimerType::Break, None)),
+            "long" => Some((TimerType::LongBreak, None)),
+            name => self
+                .kinds
+                .get(name)
+                .map(|kind| (kind.timer_type, Some(name.to_string()))),
+        }
+    }
+
+    // the first kind the sequence uses that isn't defined
+    pub fn unknown_kind(&self) -> Option<&str> {
+        let sequence = self.sequence.as_ref()?;
+
+        sequence
+            .steps()
+            .iter()
+            .map(|step| step.kind.as_str())
+            .find(|kind| self.resolve_kind(kind).is_none())
+    }
+
+    pub fn prefix(&self, kind: &str) -> Option<&str> {
+        self.kinds.get(kind)?.prefix.as_deref()
+    }
+
+    // the timer for a step of the sequence, an unknown kind is taken to be work
+    fn step_timer(&self, step: &Step) -> Timer {
+        let (timer_type, kind) = self
+            .resolve_kind(&step.kind)
+            .unwrap_or((TimerType::Work, None));
+
+        let mut timer = new_timer(timer_type, step.duration);
+        timer.set_kind(kind);
+        timer
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            work_duration: Duration::from_secs(25 * 60),
+            break_duration: Duration::from_secs(5 * 60),
+            long_break_duration: Duration::from_secs(15 * 60),
+            long_break_interval: 4,
+            count_skipped: false,
+            suspend: SuspendPolicy::Count,
+            auto: false,
+            notifications: true,
+            sequence: None,
+            kinds: BTreeMap::new(),
+        }
+    }
 }
 
 pub struct Controller {
@@ -20,17 +154,37 @@ pub struct Controller {
     tx: flume::Sender<String>,
     rx: flume::Receiver<String>,
     config: Config,
-    event_handlers: HashMap<TimerEvent, Vec<Arc<dyn Fn(&Timer) + Send + Sync>>>,
-    num_finished_timers: u64,
+    event_handlers: HashMap<TimerEvent, Vec<EventHandler>>,
+    scheduler: Scheduler,
+    // the current step of the sequence, if there is one
+    position: usize,
+    state_file: Option<PathBuf>,
+    start_guards: Vec<StartGuard>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Snapshot {
+    pub config: Config,
+    #[serde(default)]
+    pub work: WorkCounts,
+    #[serde(default)]
+    pub position: usize,
+    pub timer: TimerSnapshot,
 }
 
 impl Controller {
     pub fn new(config: Config) -> Arc<Mutex<Controller>> {
         let (tx, rx) = flume::unbounded();
 
-        let Config { work_duration, .. } = config;
+        let timer = match &config.sequence {
+            Some(sequence) => Controller::watch_timer(
+                tx.clone(),
+                Arc::new(Mutex::new(config.step_timer(sequence.step(0)))),
+            ),
+            None => Controller::create_timer(tx.clone(), TimerType::Work, config.work_duration),
+        };
 
-        let timer = Controller::create_timer(tx.clone(), TimerType::Work, work_duration);
+        let scheduler = Scheduler::new(config.long_break_interval, config.count_skipped);
 
         Arc::new(Mutex::new(Controller {
             config,
@@ -38,7 +192,41 @@ impl Controller {
             tx,
             rx,
             event_handlers: HashMap::new(),
-            num_finished_timers: 0,
+            scheduler,
+            position: 0,
+            state_file: None,
+            start_guards: Vec::new(),
+        }))
+    }
+
+    // the restored timer is paused or finished, use start or resume to continue it
+    pub fn restore(snapshot: Snapshot) -> Arc<Mutex<Controller>> {
+        let (tx, rx) = flume::unbounded();
+
+        let timer = Controller::watch_timer(tx.clone(), Timer::restore(&snapshot.timer));
+
+        {
+            let mut timer = timer.lock().expect("Failed to lock timer");
+            let cycle = cycle(&timer, snapshot.work);
+            timer.set_cycle(cycle);
+        }
+
+        let scheduler = Scheduler::restore(
+            snapshot.config.long_break_interval,
+            snapshot.config.count_skipped,
+            snapshot.work,
+        );
+
+        Arc::new(Mutex::new(Controller {
+            config: snapshot.config,
+            timer,
+            tx,
+            rx,
+            event_handlers: HashMap::new(),
+            scheduler,
+            position: snapshot.position,
+            state_file: None,
+            start_guards: Vec::new(),
         }))
     }
 
@@ -48,8 +236,10 @@ impl Controller {
         duration: Duration,
     ) -> Arc<Mutex<Timer>> {
         // create a new timer
-        let timer = Timer::new(timer_type, &duration.clone());
+        Controller::watch_timer(tx, Timer::new(timer_type, &duration))
+    }
 
+    fn watch_timer(tx: flume::Sender<String>, timer: Arc<Mutex<Timer>>) -> Arc<Mutex<Timer>> {
         let mut timer_guard = timer.lock().expect("Failed to lock timer");
 
         // add Finish event handler (only used for auto mode to start next timer)
@@ -77,43 +267,201 @@ impl Controller {
         }
     }
 
-    pub fn start(controller: &Arc<Mutex<Self>>) {
-        let controller = Arc::clone(controller);
+    // a new timer has to get past the start guards, a restored one is simply resumed
+    pub fn start(controller: &Arc<Mutex<Self>>) -> Result<(), NotStarted> {
+        Controller::approve(controller)?;
+        Controller::listen(controller);
+        Controller::resume(controller);
 
-        let mut controller_guard_1 = controller.lock().expect("Failed to lock controller");
-        let rx = controller_guard_1.rx.clone();
+        Ok(())
+    }
+
+    // runs the start guards for the current timer if it is new, without starting it
+    pub fn approve(controller: &Arc<Mutex<Self>>) -> Result<(), NotStarted> {
+        let timer = Controller::get_current_timer(controller);
+        let mut proposed = timer.lock().expect("Failed to lock timer").clone();
+
+        if proposed.is_new() {
+            Controller::check_start(controller, &mut proposed)?;
+
+            let mut timer = timer.lock().expect("Failed to lock timer");
+            timer.set_duration(proposed.duration());
+            timer.set_label(proposed.label().map(String::from));
+        }
+
+        Ok(())
+    }
+
+    pub fn before_start(controller: &Arc<Mutex<Self>>, guard: StartGuard) {
+        let mut controller = controller.lock().expect("Failed to lock controller");
+        controller.start_guards.push(guard);
+    }
+
+    // runs the start guards without holding the controller, so slow ones don't hold up commands
+    fn check_start(controller: &Arc<Mutex<Self>>, timer: &mut Timer) -> Result<(), NotStarted> {
+        let guards = controller
+            .lock()
+            .expect("Failed to lock controller")
+            .start_guards
+            .clone();
+
+        for guard in guards {
+            guard(timer).map_err(NotStarted::Cancelled)?;
+        }
+
+        Ok(())
+    }
 
-        controller_guard_1.start_current_timer();
+    // handle messages from timers without touching the current timer
+    pub fn listen(controller: &Arc<Mutex<Self>>) {
+        let controller_guard_1 = controller.lock().expect("Failed to lock controller");
+        let rx = controller_guard_1.rx.clone();
         drop(controller_guard_1);
 
+        // hold on to the controller weakly, so the loop ends once it has been dropped
+        let controller = Arc::downgrade(controller);
+
+        task::spawn(async move {
+            while let Ok(msg) = rx.recv_async().await {
+                let Some(controller) = controller.upgrade() else {
+                    return;
+                };
+
+                // if Controller is in auto mode, start next timer
+                let auto = {
+                    let controller_guard_2 = controller.lock().expect("Failed to lock controller");
+                    controller_guard_2.checkpoint();
+                    controller_guard_2.config.auto
+                };
+
+                let next = match msg.as_str() {
+                    "timer_finished" => auto,
+                    "skip" => true,
+                    _ => false,
+                };
+
+                if next {
+                    // the start guards block, e.g. while they wait for before hooks
+                    let next = task::spawn_blocking(move || Controller::next(&controller));
+
+                    if let Ok(Err(reason)) = next.await {
+                        eprintln!("The next timer was not started: {}", reason);
+                    }
+                }
+            }
+        });
+    }
+
+    // save the state to `path` on every transition, and periodically while a timer is running
+    pub fn persist(controller: &Arc<Mutex<Self>>, path: PathBuf) {
+        let mut controller_guard = controller.lock().expect("Failed to lock controller");
+        controller_guard.state_file = Some(path);
+        controller_guard.checkpoint();
+        drop(controller_guard);
+
+        let controller = Arc::downgrade(controller);
+
         task::spawn(async move {
+            let mut interval = tokio::time::interval(CHECKPOINT_INTERVAL);
+
             loop {
-                let msg = rx
-                    .recv_async()
-                    .await
-                    .expect("Failed to listen to socket messages");
+                interval.tick().await;
 
-                let mut controller_guard_2 = controller.lock().expect("Failed to lock controller");
+                let Some(controller) = controller.upgrade() else {
+                    return;
+                };
 
-                match msg.as_str() {
-                    "timer_finished" => {
-                        controller_guard_2.on_timer_finished();
-                    }
-                    "skip" => {
-                        controller_guard_2.start_next_timer();
-                    }
-                    _ => {}
+                let controller = controller.lock().expect("Failed to lock controller");
+                let timer_state = controller
+                    .timer
+                    .lock()
+                    .expect("Failed to lock timer")
+                    .state();
+
+                if timer_state == TimerState::Running {
+                    controller.checkpoint();
                 }
+            }
+        });
+    }
+
+    // apply the suspend policy to the current timer whenever the system wakes up from suspend
+    pub fn detect_suspend(controller: &Arc<Mutex<Self>>) {
+        let controller = Arc::downgrade(controller);
+
+        task::spawn(async move {
+            let mut detector = SuspendDetector::new();
+            let mut interval = tokio::time::interval(SUSPEND_CHECK_INTERVAL);
+
+            loop {
+                interval.tick().await;
 
-                drop(controller_guard_2);
+                let Some(controller) = controller.upgrade() else {
+                    return;
+                };
+
+                if let Some(gap) = detector.check() {
+                    Controller::suspended(&controller, gap);
+                }
             }
         });
     }
 
+    // the system was suspended for `gap`
+    pub fn suspended(controller: &Arc<Mutex<Self>>, gap: Duration) {
+        let controller = controller.lock().expect("Failed to lock controller");
+        Timer::suspended(&controller.timer, gap, controller.config.suspend);
+        controller.checkpoint();
+    }
+
+    pub fn config(controller: &Arc<Mutex<Self>>) -> Config {
+        let controller = controller.lock().expect("Failed to lock controller");
+        controller.config.clone()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            config: self.config.clone(),
+            work: self.scheduler.counts(),
+            position: self.position,
+            timer: self.timer.lock().expect("Failed to lock timer").snapshot(),
+        }
+    }
+
+    fn checkpoint(&self) {
+        if let Some(path) = &self.state_file {
+            if let Err(err) = state::save(path, &SavedState::new(self.snapshot())) {
+                eprintln!("Failed to save state: {}", err);
+            }
+        }
+    }
+
     // TODO: These methods look like they can be refactored into a single method
-    pub fn next(controller: &Arc<Mutex<Self>>) {
+    // returns why the next timer may not start, the current one is left as it is then
+    pub fn next(controller: &Arc<Mutex<Self>>) -> Result<(), NotStarted> {
+        let controller_guard = controller.lock().expect("Failed to lock controller");
+        let current = Arc::clone(&controller_guard.timer);
+        let ending = ending(&current.lock().expect("Failed to lock timer"));
+        let next = controller_guard.next_timer(ending);
+        drop(controller_guard);
+
+        let mut next = next.ok_or(NotStarted::SequenceComplete)?;
+
+        Controller::check_start(controller, &mut next)?;
+
+        let mut controller = controller.lock().expect("Failed to lock controller");
+
+        // another command moved on to the next timer in the meantime
+        if Arc::ptr_eq(&current, &controller.timer) {
+            controller.start_timer(next);
+        }
+
+        Ok(())
+    }
+
+    pub fn resume(controller: &Arc<Mutex<Self>>) {
         let mut controller = controller.lock().expect("Failed to lock controller");
-        controller.start_next_timer();
+        controller.start_current_timer();
     }
 
     pub fn stop(controller: &Arc<Mutex<Self>>) {
@@ -126,63 +474,145 @@ impl Controller {
         controller.pause_current_timer();
     }
 
+    // a finished timer that is given more time is started again
+    pub fn adjust(controller: &Arc<Mutex<Self>>, adjustment: Adjustment, amount: Duration) {
+        let mut controller = controller.lock().expect("Failed to lock controller");
+        controller.adjust_current_timer(adjustment, amount);
+    }
+
+    // the label, and the task it stands for if it's one from the task list, stay with the timers
+    // that follow until they are changed again
+    pub fn label(controller: &Arc<Mutex<Self>>, label: Option<String>, task: Option<u64>) {
+        let controller = controller.lock().expect("Failed to lock controller");
+
+        let mut timer = controller.timer.lock().expect("Failed to lock timer");
+        timer.set_label(label);
+        timer.set_task(task);
+        drop(timer);
+
+        controller.checkpoint();
+    }
+
+    // only work timers that haven't finished can be interrupted, returns false for others
+    pub fn interrupt(
+        controller: &Arc<Mutex<Self>>,
+        interruption: Interruption,
+        pause: bool,
+    ) -> bool {
+        let mut controller = controller.lock().expect("Failed to lock controller");
+
+        let mut timer = controller.timer.lock().expect("Failed to lock timer");
+
+        if timer.timer_type() != TimerType::Work || timer.state() == TimerState::Stopped {
+            return false;
+        }
+
+        timer.interrupt(interruption);
+        drop(timer);
+
+        if pause {
+            controller.pause_current_timer();
+        } else {
+            controller.checkpoint();
+        }
+
+        true
+    }
+
     fn start_current_timer(&mut self) {
         Timer::start(&self.timer);
+        self.checkpoint();
     }
 
     fn stop_current_timer(&mut self) {
         let mut timer = self.timer.lock().expect("Failed to lock timer");
         timer.stop();
+        drop(timer);
+
+        self.checkpoint();
     }
 
-    fn pause_current_timer(&mut self) {
-        let mut timer = self.timer.lock().expect("Failed to lock timer");
-        timer.pause();
+    fn adjust_current_timer(&mut self, adjustment: Adjustment, amount: Duration) {
+        Timer::adjust(&self.timer, adjustment, amount);
+        self.checkpoint();
     }
 
-    fn start_next_timer(&mut self) {
-        self.num_finished_timers += 1;
+    fn skip_current_timer(&mut self) {
+        let mut timer = self.timer.lock().expect("Failed to lock timer");
 
-        self.stop_current_timer();
+        // a finished timer has nothing left to skip
+        if timer.state() != TimerState::Stopped {
+            timer.skip();
+        }
+    }
 
-        let current_timer = self.timer.lock().expect("Failed to lock timer");
+    fn pause_current_timer(&mut self) {
+        let mut timer = self.timer.lock().expect("Failed to lock timer");
+        timer.pause();
+        drop(timer);
 
-        let new_timer = match current_timer.timer_type() {
-            TimerType::Work => {
-                let mut duration = self.config.break_duration.clone();
+        self.checkpoint();
+    }
 
-                let num_finished_break_timers = self.num_finished_timers / 2;
+    fn start_timer(&mut self, timer: Timer) {
+        let current = self.timer.lock().expect("Failed to lock timer");
 
-                if num_finished_break_timers != 0
-                    && num_finished_break_timers % (self.config.long_break_interval - 1) == 0
-                {
-                    duration = self.config.long_break_duration.clone();
-                }
+        if is_pomodoro(&current) {
+            self.scheduler.work_ended(ending(&current));
+        }
+        drop(current);
 
-                Controller::create_timer(self.tx.clone(), TimerType::Break, duration)
-            }
-            TimerType::Break => Controller::create_timer(
-                self.tx.clone(),
-                TimerType::Work,
-                self.config.work_duration.clone(),
-            ),
-        };
+        if timer.timer_type() == TimerType::LongBreak {
+            self.scheduler.long_break_started();
+        }
+
+        if let Some(sequence) = &self.config.sequence {
+            self.position = sequence.next_position(self.position).unwrap_or(0);
+        }
 
-        drop(current_timer);
+        self.skip_current_timer();
 
-        self.timer = new_timer;
+        self.timer = Controller::watch_timer(self.tx.clone(), Arc::new(Mutex::new(timer)));
 
         self.attach_timer_handlers();
 
         self.start_current_timer();
     }
 
-    fn on_timer_finished(&mut self) {
-        // if Controller is in auto mode, start next timer
-        if self.config.auto {
-            self.start_next_timer();
-            return;
+    // the timer that follows the current one if it ends this way, not started yet. None once a
+    // sequence that doesn't repeat is done.
+    fn next_timer(&self, ending: Ending) -> Option<Timer> {
+        let current_timer = self.timer.lock().expect("Failed to lock timer");
+
+        let mut timer = match &self.config.sequence {
+            Some(sequence) => self
+                .config
+                .step_timer(sequence.step(sequence.next_position(self.position)?)),
+            None => match current_timer.timer_type() {
+                TimerType::Work => match self.scheduler.break_after(ending) {
+                    TimerType::LongBreak => {
+                        new_timer(TimerType::LongBreak, self.config.long_break_duration)
+                    }
+                    _ => new_timer(TimerType::Break, self.config.break_duration),
+                },
+                TimerType::Break | TimerType::LongBreak => {
+                    new_timer(TimerType::Work, self.config.work_duration)
+                }
+            },
+        };
+
+        timer.set_label(current_timer.label().map(String::from));
+        timer.set_task(current_timer.task());
+
+        // the current work timer will have ended by then
+        let mut work = self.scheduler.counts();
+        if is_pomodoro(&current_timer) {
+            work.completed += 1;
         }
+        let cycle = cycle(&timer, work);
+        timer.set_cycle(cycle);
+
+        Some(timer)
     }
 
     pub fn get_current_timer(controller: &Arc<Mutex<Self>>) -> Arc<Mutex<Timer>> {
@@ -194,11 +624,54 @@ impl Controller {
         timer
     }
 
-    pub fn on(
-        controller: &Arc<Mutex<Self>>,
-        event: TimerEvent,
-        callback: Arc<dyn Fn(&Timer) + Send + Sync>,
-    ) {
+    pub fn status(controller: &Arc<Mutex<Self>>) -> Status {
+        let controller = controller.lock().expect("Failed to lock controller");
+        // as if the current timer runs to its end
+        let next_timer = controller.next_timer(Ending::Finished);
+
+        let timer = controller.timer.lock().expect("Failed to lock timer");
+        let remaining = timer.time_left();
+
+        // at the end of a sequence there is no next timer, and no next duration
+        let (next_timer_type, next_duration) = match &next_timer {
+            Some(next_timer) => (next_timer.timer_type(), next_timer.duration()),
+            None => (timer.timer_type(), Duration::ZERO),
+        };
+
+        Status {
+            timer_type: timer.timer_type(),
+            state: timer.state(),
+            remaining_secs: remaining.as_secs(),
+            total_secs: timer.duration().as_secs(),
+            elapsed_secs: timer.duration().saturating_sub(remaining).as_secs(),
+            cycle: timer.cycle(),
+            long_break_interval: controller.config.long_break_interval,
+            completed: controller.scheduler.counts().completed,
+            skipped: controller.scheduler.counts().skipped,
+            cycle_position: controller.scheduler.position(timer.timer_type()),
+            next_timer_type,
+            next_duration_secs: next_duration.as_secs(),
+            auto: controller.config.auto,
+            label: timer.label().map(String::from),
+            interruptions: timer.interruptions(),
+            sequence: controller
+                .config
+                .sequence
+                .as_ref()
+                .map(|sequence| SequenceStatus {
+                    step: controller.position % sequence.steps().len() + 1,
+                    steps: sequence.steps().len(),
+                    repeat: sequence.repeats(),
+                }),
+            kind: timer.kind().map(String::from),
+            prefix: timer
+                .kind()
+                .and_then(|kind| controller.config.prefix(kind))
+                .map(String::from),
+        }
+    }
+
+    pub fn on(controller: &Arc<Mutex<Self>>, event: TimerEvent, callback: EventHandler) {
         let mut controller = controller.lock().expect("Failed to lock controller");
 
         // save the handler for future timers
@@ -215,3 +688,35 @@ impl Controller {
         timer.on(event, callback);
     }
 }
+
+// a timer that isn't started or watched yet
+fn new_timer(timer_type: TimerType, duration: Duration) -> Timer {
+    Timer::new(timer_type, &duration)
+        .lock()
+        .expect("Failed to lock timer")
+        .clone()
+}
+
+// a timer that is skipped or stopped before its time is up doesn't count as finished
+fn ending(timer: &Timer) -> Ending {
+    match timer.time_left().is_zero() {
+        true => Ending::Finished,
+        false => Ending::Skipped,
+    }
+}
+
+// timers of a kind, such as meetings, aren't pomodoros
+fn is_pomodoro(timer: &Timer) -> bool {
+    timer.timer_type() == TimerType::Work && timer.kind().is_none()
+}
+
+// every work timer starts a pomodoro, and the timers after it belong to the same one, given the
+// work timers that ended before the timer
+fn cycle(timer: &Timer, work: WorkCounts) -> u64 {
+    let ended = work.completed + work.skipped;
+
+    match is_pomodoro(timer) {
+        true => ended + 1,
+        false => ended.max(1),
+    }
+}
@@ -0,0 +1,527 @@
+use crate::controller::{Config, Controller, NotStarted, Snapshot};
+use crate::paths;
+use crate::peer;
+use crate::protocol::{
+    self, Command, ErrorCode, Event, EventKind, Reply, Request, Response, SessionStatus,
+};
+use crate::state;
+use crate::timer::{Interruption, Timer, TimerEvent, TimerState};
+use std::collections::BTreeMap;
+use std::io::BufReader;
+use std::ops::ControlFlow;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const DEFAULT_SESSION: &str = "default";
+
+// how often subscribers that asked for ticks get one
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+// called for every new session before its timer starts, e.g. to attach event handlers or start
+// guards
+pub type SessionHandler = Arc<dyn Fn(&str, &Arc<Mutex<Controller>>) + Send + Sync>;
+
+type Subscribers = Arc<Mutex<Vec<Subscriber>>>;
+
+// a connection that streams events, of one session or of all of them
+struct Subscriber {
+    session: Option<String>,
+    tx: flume::Sender<(String, EventKind, Arc<Mutex<Controller>>)>,
+}
+
+// serves the control socket and runs any number of named sessions, each with its own controller
+pub struct Daemon {
+    sessions: Mutex<BTreeMap<String, Arc<Mutex<Controller>>>>,
+    // adds the start guards, the timer only starts once it got past them
+    guard_session: SessionHandler,
+    on_session: SessionHandler,
+    subscribers: Subscribers,
+    shutdown: flume::Sender<()>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl Daemon {
+    // the receiver gets a message once the daemon should exit, after its last session stopped
+    pub fn new(
+        guard_session: SessionHandler,
+        on_session: SessionHandler,
+    ) -> (Arc<Daemon>, flume::Receiver<()>) {
+        let (shutdown, shutdown_rx) = flume::bounded(1);
+
+        let daemon = Arc::new(Daemon {
+            sessions: Mutex::new(BTreeMap::new()),
+            guard_session,
+            on_session,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            shutdown,
+            runtime: tokio::runtime::Handle::current(),
+        });
+
+        (daemon, shutdown_rx)
+    }
+
+    pub fn start_session(
+        &self,
+        name: &str,
+        config: Config,
+        snapshot: Option<Snapshot>,
+        label: Option<String>,
+        task: Option<u64>,
+        replace: bool,
+    ) -> Result<(), protocol::Error> {
+        validate_session_name(name)?;
+
+        if !replace {
+            self.check_session_free(name)?;
+        }
+
+        let auto = config.auto;
+        let restored_state = snapshot.as_ref().map(|snapshot| snapshot.timer.state);
+
+        let controller = match snapshot {
+            Some(mut snapshot) => {
+                snapshot.config = config;
+                Controller::restore(snapshot)
+            }
+            None => Controller::new(config),
+        };
+
+        if label.is_some() {
+            Controller::label(&controller, label, task);
+        }
+
+        // the start guards may take a while, the sessions aren't locked meanwhile and a session
+        // that is replaced keeps running in case they cancel the timer
+        (self.guard_session)(name, &controller);
+        Controller::approve(&controller).map_err(not_started)?;
+
+        (self.on_session)(name, &controller);
+        self.publish_events(name, &controller);
+        Controller::listen(&controller);
+
+        let replaced = {
+            let mut sessions = self.sessions.lock().expect("Failed to lock sessions");
+
+            // another client started a timer with the same name in the meantime, this one never
+            // ran and is simply dropped
+            if !replace && sessions.contains_key(name) {
+                drop(sessions);
+                return self.check_session_free(name);
+            }
+
+            sessions.insert(name.to_string(), Arc::clone(&controller))
+        };
+
+        if let Some(replaced) = replaced {
+            Controller::stop(&replaced);
+        }
+
+        match restored_state {
+            None | Some(TimerState::Running) => Controller::resume(&controller),
+            // a paused timer waits for 'pomo resume'
+            Some(TimerState::Paused) => {}
+            Some(TimerState::Stopped) => {
+                if auto {
+                    if let Err(reason) = Controller::next(&controller) {
+                        eprintln!("The next timer was not started: {}", reason);
+                    }
+                }
+            }
+        }
+
+        // checkpoint the controller so it can be restored if this process dies
+        Controller::persist(&controller, paths::state_file(name));
+        Controller::detect_suspend(&controller);
+
+        Ok(())
+    }
+
+    fn check_session_free(&self, name: &str) -> Result<(), protocol::Error> {
+        let sessions = self.sessions.lock().expect("Failed to lock sessions");
+
+        if sessions.contains_key(name) {
+            return Err(protocol::Error::new(
+                ErrorCode::SessionExists,
+                &format!("A timer named '{}' is already running.", name),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn serve(self: &Arc<Self>, listener: UnixListener) {
+        let daemon = Arc::clone(self);
+
+        // every client gets its own thread so a long lived connection like 'pomo attach' doesn't
+        // block others
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let daemon = Arc::clone(&daemon);
+
+                        std::thread::spawn(move || {
+                            // timers are spawned on the runtime
+                            let _guard = daemon.runtime.enter();
+
+                            if let ControlFlow::Break(()) = daemon.handle_connection(stream) {
+                                let _ = daemon.shutdown.send(());
+                            }
+                        });
+                    }
+
+                    Err(err) => {
+                        eprintln!("Error: {:?}", err);
+                        let _ = daemon.shutdown.send(());
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    fn publish_events(&self, name: &str, controller: &Arc<Mutex<Controller>>) {
+        for event in TimerEvent::ALL {
+            let name = name.to_string();
+            let subscribers = Arc::clone(&self.subscribers);
+            let controller_ref = Arc::downgrade(controller);
+
+            Controller::on(
+                controller,
+                event,
+                Arc::new(move |_: &Timer| {
+                    let Some(controller) = controller_ref.upgrade() else {
+                        return;
+                    };
+
+                    let mut subscribers = subscribers.lock().expect("Failed to lock subscribers");
+
+                    // the controller is locked while its event handlers run, subscribers look
+                    // at it once they write the event, and forget those that have hung up
+                    subscribers.retain(|subscriber| {
+                        if subscriber
+                            .session
+                            .as_ref()
+                            .is_some_and(|session| *session != name)
+                        {
+                            return true;
+                        }
+
+                        let message = (
+                            name.clone(),
+                            EventKind::Timer(event),
+                            Arc::clone(&controller),
+                        );
+                        subscriber.tx.send(message).is_ok()
+                    });
+                }),
+            );
+        }
+    }
+
+    fn handle_connection(&self, stream: UnixStream) -> ControlFlow<()> {
+        let mut reader = BufReader::new(&stream);
+        let mut writer = &stream;
+
+        // the socket is private already, but don't take orders from other users if it ever isn't
+        if peer::peer_uid(&stream).ok() != Some(peer::current_uid()) {
+            let response = Response::error(
+                None,
+                ErrorCode::PermissionDenied,
+                "Pomo only accepts connections from the user running it.",
+            );
+            let _ = protocol::write_message(&mut writer, &response);
+            return ControlFlow::Continue(());
+        }
+
+        // a connection may carry several requests, answer each of them until the client hangs up
+        while let Ok(Some(line)) = protocol::read_message(&mut reader) {
+            let request = match Request::parse(&line) {
+                Ok(request) => request,
+                Err(response) => {
+                    let _ = protocol::write_message(&mut writer, &response);
+                    continue;
+                }
+            };
+
+            if let Command::Subscribe { ticks, all } = request.command {
+                self.stream_events(&mut writer, &request, ticks, all);
+                break;
+            }
+
+            let (response, flow) = match self.handle(request.session.as_deref(), request.command) {
+                Ok((reply, flow)) => (Response::result(request.id, reply), flow),
+                Err(error) => (
+                    Response::error(Some(request.id), error.code, &error.message),
+                    ControlFlow::Continue(()),
+                ),
+            };
+
+            let _ = protocol::write_message(&mut writer, &response);
+
+            if flow.is_break() {
+                return flow;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    // write events until the client hangs up, or the session it watches is stopped
+    fn stream_events(&self, writer: &mut &UnixStream, request: &Request, ticks: bool, all: bool) {
+        let id = request.id;
+
+        // None watches all sessions
+        let session = if all {
+            None
+        } else {
+            match self.session(request.session.as_deref()) {
+                Ok((name, _)) => Some(name),
+                Err(error) => {
+                    let response = Response::error(Some(id), error.code, &error.message);
+                    let _ = protocol::write_message(writer, &response);
+                    return;
+                }
+            }
+        };
+
+        let (tx, rx) = flume::unbounded();
+
+        let subscriber = Subscriber {
+            session: session.clone(),
+            tx,
+        };
+        self.subscribers
+            .lock()
+            .expect("Failed to lock subscribers")
+            .push(subscriber);
+
+        if protocol::write_message(writer, &Response::result(id, Reply::Ok)).is_err() {
+            return;
+        }
+
+        let send_all = |writer: &mut &UnixStream, kind: EventKind| {
+            self.watched_sessions(session.as_deref())
+                .iter()
+                .all(|(name, controller)| send_event(writer, name, kind, controller))
+        };
+
+        if !send_all(writer, EventKind::Status) {
+            return;
+        }
+
+        loop {
+            let message = if ticks {
+                rx.recv_timeout(TICK_INTERVAL)
+            } else {
+                rx.recv().map_err(|_| flume::RecvTimeoutError::Disconnected)
+            };
+
+            let sent = match message {
+                Ok((name, kind, controller)) => {
+                    let sent = send_event(writer, &name, kind, &controller);
+
+                    // a stopped session has no more events
+                    if session.is_some() && kind == EventKind::Timer(TimerEvent::Stop) {
+                        return;
+                    }
+
+                    sent
+                }
+                Err(flume::RecvTimeoutError::Timeout) => send_all(writer, EventKind::Tick),
+                Err(flume::RecvTimeoutError::Disconnected) => return,
+            };
+
+            if !sent {
+                return;
+            }
+        }
+    }
+
+    fn watched_sessions(&self, session: Option<&str>) -> Vec<(String, Arc<Mutex<Controller>>)> {
+        let sessions = self.sessions.lock().expect("Failed to lock sessions");
+
+        sessions
+            .iter()
+            .filter(|(name, _)| session.is_none_or(|session| session == name.as_str()))
+            .map(|(name, controller)| (name.clone(), Arc::clone(controller)))
+            .collect()
+    }
+
+    fn handle(
+        &self,
+        session: Option<&str>,
+        command: Command,
+    ) -> Result<(Reply, ControlFlow<()>), protocol::Error> {
+        let reply = match command {
+            Command::Subscribe { .. } => {
+                unreachable!("Subscriptions are handled by the connection")
+            }
+            Command::Start {
+                config,
+                snapshot,
+                replace,
+                label,
+                task,
+            } => {
+                let name = session.unwrap_or(DEFAULT_SESSION);
+                self.start_session(
+                    name,
+                    config,
+                    snapshot.map(|snapshot| *snapshot),
+                    label,
+                    task,
+                    replace,
+                )?;
+                Reply::Ok
+            }
+            Command::Label { label, task } => {
+                Controller::label(&self.session(session)?.1, label, task);
+                Reply::Ok
+            }
+            Command::Interrupt { kind, note, pause } => {
+                let interruption = Interruption { kind, note };
+
+                if !Controller::interrupt(&self.session(session)?.1, interruption, pause) {
+                    return Err(protocol::Error::new(
+                        ErrorCode::InvalidState,
+                        "Only a work timer that hasn't finished can be interrupted.",
+                    ));
+                }
+
+                Reply::Ok
+            }
+            Command::List => {
+                let sessions = self.sessions.lock().expect("Failed to lock sessions");
+
+                Reply::Sessions {
+                    sessions: sessions
+                        .iter()
+                        .map(|(name, controller)| SessionStatus {
+                            name: name.clone(),
+                            status: Controller::status(controller),
+                        })
+                        .collect(),
+                }
+            }
+            Command::Pause => {
+                Controller::pause(&self.session(session)?.1);
+                Reply::Ok
+            }
+            Command::Resume => {
+                Controller::resume(&self.session(session)?.1);
+                Reply::Ok
+            }
+            Command::Next => {
+                Controller::next(&self.session(session)?.1).map_err(not_started)?;
+                Reply::Ok
+            }
+            Command::Adjust {
+                adjustment,
+                duration,
+            } => {
+                Controller::adjust(&self.session(session)?.1, adjustment, duration);
+                Reply::Ok
+            }
+            Command::Status => {
+                Reply::Status(Box::new(Controller::status(&self.session(session)?.1)))
+            }
+            Command::Stop => {
+                let (name, controller) = self.session(session)?;
+
+                Controller::stop(&controller);
+
+                // a stopped timer is not meant to be restored
+                state::remove(&paths::state_file(&name));
+
+                let mut sessions = self.sessions.lock().expect("Failed to lock sessions");
+                sessions.remove(&name);
+
+                // end the program when the last session is stopped
+                if sessions.is_empty() {
+                    return Ok((Reply::Ok, ControlFlow::Break(())));
+                }
+
+                Reply::Ok
+            }
+        };
+
+        Ok((reply, ControlFlow::Continue(())))
+    }
+
+    // without a name, the default session is used, or the only session if there is just one
+    fn session(
+        &self,
+        name: Option<&str>,
+    ) -> Result<(String, Arc<Mutex<Controller>>), protocol::Error> {
+        let sessions = self.sessions.lock().expect("Failed to lock sessions");
+
+        let name = match name {
+            Some(name) => name.to_string(),
+            None if sessions.contains_key(DEFAULT_SESSION) || sessions.len() != 1 => {
+                DEFAULT_SESSION.to_string()
+            }
+            None => sessions.keys().next().cloned().unwrap_or_default(),
+        };
+
+        match sessions.get(&name) {
+            Some(controller) => Ok((name, Arc::clone(controller))),
+            None if sessions.is_empty() => Err(protocol::Error::new(
+                ErrorCode::SessionNotFound,
+                "No timers are running.",
+            )),
+            None => Err(protocol::Error::new(
+                ErrorCode::SessionNotFound,
+                &format!(
+                    "There is no timer named '{}', running timers: {}.",
+                    name,
+                    sessions.keys().cloned().collect::<Vec<_>>().join(", ")
+                ),
+            )),
+        }
+    }
+}
+
+// session names end up in file names, so keep them simple
+pub fn validate_session_name(name: &str) -> Result<(), protocol::Error> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid {
+        return Err(protocol::Error::new(
+            ErrorCode::InvalidSessionName,
+            &format!(
+                "Invalid timer name '{}', use letters, digits, '-' and '_'.",
+                name
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn not_started(err: NotStarted) -> protocol::Error {
+    match err {
+        NotStarted::Cancelled(reason) => protocol::Error::new(
+            ErrorCode::Cancelled,
+            &format!("The timer was not started: {}", reason),
+        ),
+        NotStarted::SequenceComplete => {
+            protocol::Error::new(ErrorCode::InvalidState, &err.to_string())
+        }
+    }
+}
+
+// returns false once the client has hung up
+fn send_event(
+    writer: &mut &UnixStream,
+    session: &str,
+    kind: EventKind,
+    controller: &Arc<Mutex<Controller>>,
+) -> bool {
+    let event = Event::new(session, kind, Controller::status(controller));
+    protocol::write_message(writer, &event).is_ok()
+}
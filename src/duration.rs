@@ -0,0 +1,173 @@
+use serde::de::{self, Deserializer, Visitor};
+use std::fmt;
+use std::time::Duration;
+
+// shown in error messages
+pub const EXAMPLES: &str = "90s, 25m, 1h15m or 1:30:00";
+
+const SECOND: Duration = Duration::from_secs(1);
+const MINUTE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DurationError {
+    Invalid(String),
+    // no timer, adjustment or timeout can be zero long
+    Zero(String),
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DurationError::Invalid(input) => write!(
+                f,
+                "Invalid duration '{}', use for example {}.",
+                input, EXAMPLES
+            ),
+            DurationError::Zero(input) => {
+                write!(f, "Invalid duration '{}', it can't be zero.", input)
+            }
+        }
+    }
+}
+
+// Parses durations such as "90s", "25m", "1h15m", "1:30:00" (h:mm:ss) and "25:00" (mm:ss). A bare
+// number is a number of minutes. Zero is refused.
+pub fn parse(input: &str) -> Result<Duration, DurationError> {
+    parse_in(input, MINUTE)
+}
+
+// like `parse`, but a bare number is a number of `unit`s
+fn parse_in(input: &str, unit: Duration) -> Result<Duration, DurationError> {
+    let trimmed = input.trim();
+    let error = || DurationError::Invalid(input.to_string());
+
+    if trimmed.is_empty() {
+        return Err(error());
+    }
+
+    let secs = if let Ok(number) = trimmed.parse::<u64>() {
+        number.checked_mul(unit.as_secs())
+    } else if trimmed.contains(':') {
+        parse_clock(trimmed)
+    } else {
+        parse_units(trimmed)
+    };
+
+    match secs.ok_or_else(error)? {
+        0 => Err(DurationError::Zero(input.to_string())),
+        secs => Ok(Duration::from_secs(secs)),
+    }
+}
+
+// "1:30:00" or "25:00"
+fn parse_clock(input: &str) -> Option<u64> {
+    let parts = input
+        .split(':')
+        .map(|part| match part.len() {
+            1 | 2 => part.parse::<u64>().ok(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let (hours, minutes, secs) = match parts.as_slice() {
+        [minutes, secs] => (0, *minutes, *secs),
+        [hours, minutes, secs] if *minutes < 60 => (*hours, *minutes, *secs),
+        _ => return None,
+    };
+
+    if secs >= 60 {
+        return None;
+    }
+
+    Some(hours * 3600 + minutes * 60 + secs)
+}
+
+// "1h15m30s", each unit at most once and in this order
+fn parse_units(input: &str) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    let mut units = ['h', 'm', 's'].iter();
+
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let value: u64 = number.parse().ok()?;
+        number.clear();
+
+        let unit = units.find(|unit| **unit == c)?;
+        let multiplier = match unit {
+            'h' => 3600,
+            'm' => 60,
+            _ => 1,
+        };
+
+        total = total.checked_add(value.checked_mul(multiplier)?)?;
+    }
+
+    // a trailing number without a unit
+    if !number.is_empty() {
+        return None;
+    }
+
+    Some(total)
+}
+
+// Durations in the config file and the protocol are whole seconds. They are read from a number
+// (of seconds, or of minutes for the timer lengths in the config file) or from text such as "25m".
+struct DurationVisitor {
+    unit: Duration,
+}
+
+impl<'de> Visitor<'de> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a duration such as {}", EXAMPLES)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Duration, E> {
+        match value.checked_mul(self.unit.as_secs()) {
+            Some(0) => Err(E::custom(DurationError::Zero(value.to_string()))),
+            Some(secs) => Ok(Duration::from_secs(secs)),
+            None => Err(E::custom(DurationError::Invalid(value.to_string()))),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Duration, E> {
+        match u64::try_from(value) {
+            Ok(value) => self.visit_u64(value),
+            Err(_) => Err(E::custom(DurationError::Invalid(value.to_string()))),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Duration, E> {
+        parse_in(value, self.unit).map_err(E::custom)
+    }
+}
+
+// for `#[serde(with = "duration::secs")]`
+pub mod secs {
+    use super::{DurationVisitor, SECOND};
+    use serde::{Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        deserializer.deserialize_any(DurationVisitor { unit: SECOND })
+    }
+}
+
+// for optional settings where a bare number is a number of minutes
+pub fn deserialize_minutes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserializer
+        .deserialize_any(DurationVisitor { unit: MINUTE })
+        .map(Some)
+}
@@ -0,0 +1,283 @@
+use crate::daemon::DEFAULT_SESSION;
+use crate::paths;
+use crate::timer::{Interruption, Interruptions, Timer, TimerEvent, TimerType};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    #[serde(default = "default_session")]
+    pub session: String,
+    pub event: TimerEvent,
+    pub timer_type: TimerType,
+    // a kind from the config file, such as "meeting"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub duration_secs: u64,
+    pub elapsed_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    // the interruptions of the timer so far
+    #[serde(default, skip_serializing_if = "Interruptions::is_empty")]
+    pub interruptions: Interruptions,
+    // the interruption an interrupt event is about
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interruption: Option<Interruption>,
+    // how long the system was suspended, for suspend and wake events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suspended_secs: Option<u64>,
+}
+
+impl Entry {
+    pub fn new(session: &str, event: TimerEvent, timer: &Timer) -> Entry {
+        let duration = timer.duration();
+
+        Entry {
+            time: Local::now(),
+            session: session.to_string(),
+            event,
+            timer_type: timer.timer_type(),
+            kind: timer.kind().map(String::from),
+            duration_secs: duration.as_secs(),
+            elapsed_secs: duration.saturating_sub(timer.time_left()).as_secs(),
+            label: timer.label().map(String::from),
+            interruptions: timer.interruptions(),
+            interruption: match event {
+                TimerEvent::Interrupt => timer.last_interruption().cloned(),
+                _ => None,
+            },
+            suspended_secs: match event {
+                TimerEvent::Suspend | TimerEvent::Wake => {
+                    timer.last_suspend().map(|gap| gap.as_secs())
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+fn default_session() -> String {
+    DEFAULT_SESSION.to_string()
+}
+
+// an append-only log of timer events, stored as one JSON object per line
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> History {
+        History { path }
+    }
+
+    pub fn append(&self, entry: &Entry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        file.write_all(&line)?;
+        file.sync_data()
+    }
+
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut entries = Vec::new();
+
+        for line in BufReader::new(file).lines() {
+            // skip lines that can't be parsed, e.g. a partial write from a crash
+            if let Ok(entry) = serde_json::from_str(&line?) {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new(paths::history_file())
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Totals {
+    pub pomodoros: u64,
+    pub focus_secs: u64,
+    pub interruptions: u64,
+}
+
+impl Totals {
+    fn add(&mut self, other: Totals) {
+        self.pomodoros += other.pomodoros;
+        self.focus_secs += other.focus_secs;
+        self.interruptions += other.interruptions;
+    }
+}
+
+// the timers of a kind, which don't count as pomodoros, the finished ones are counted in
+// `pomodoros` of their totals
+#[derive(Default, Clone, Copy, Debug)]
+pub struct KindTotals {
+    pub today: Totals,
+    pub week: Totals,
+    pub month: Totals,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct Stats {
+    pub today: Totals,
+    pub week: Totals,
+    pub month: Totals,
+    pub current_streak: u64,
+    pub longest_streak: u64,
+    pub completed: u64,
+    pub skipped: u64,
+    pub kinds: BTreeMap<String, KindTotals>,
+}
+
+impl Stats {
+    pub fn compute(entries: &[Entry], today: NaiveDate) -> Stats {
+        let mut stats = Stats::default();
+        let mut active_days = BTreeSet::new();
+        // sessions whose timer has finished, stopping it after that doesn't end it again
+        let mut finished = BTreeSet::new();
+
+        for entry in entries {
+            match entry.event {
+                TimerEvent::Finish => {
+                    finished.insert(entry.session.as_str());
+                }
+                TimerEvent::Stop if finished.remove(entry.session.as_str()) => continue,
+                TimerEvent::Start | TimerEvent::Resume | TimerEvent::Adjust | TimerEvent::Skip => {
+                    finished.remove(entry.session.as_str());
+                }
+                _ => {}
+            }
+
+            // a timer ends by finishing, being skipped or being stopped, count the time spent on
+            // it once it has ended and its interruptions as they happen
+            let (pomodoros, focus_secs, interruptions) = match entry.event {
+                TimerEvent::Finish => (1, entry.elapsed_secs, 0),
+                TimerEvent::Skip | TimerEvent::Stop => (0, entry.elapsed_secs, 0),
+                TimerEvent::Interrupt => (0, 0, 1),
+                _ => continue,
+            };
+            let counted = Totals {
+                pomodoros,
+                focus_secs,
+                interruptions,
+            };
+
+            let date = entry.time.date_naive();
+
+            if let Some(kind) = &entry.kind {
+                let totals = stats.kinds.entry(kind.clone()).or_default();
+                let periods = [&mut totals.today, &mut totals.week, &mut totals.month];
+                add_to_periods(periods, date, today, counted);
+                continue;
+            }
+
+            if entry.timer_type != TimerType::Work {
+                continue;
+            }
+
+            match entry.event {
+                TimerEvent::Finish => stats.completed += 1,
+                TimerEvent::Skip => stats.skipped += 1,
+                _ => {}
+            }
+
+            if pomodoros > 0 {
+                active_days.insert(date);
+            }
+
+            let periods = [&mut stats.today, &mut stats.week, &mut stats.month];
+            add_to_periods(periods, date, today, counted);
+        }
+
+        stats.longest_streak = longest_streak(&active_days);
+        stats.current_streak = current_streak(&active_days, today);
+
+        stats
+    }
+
+    // share of ended work timers that were completed rather than skipped
+    pub fn completion_ratio(&self) -> Option<f64> {
+        let total = self.completed + self.skipped;
+
+        if total == 0 {
+            return None;
+        }
+
+        Some(self.completed as f64 / total as f64)
+    }
+}
+
+// adds to the totals of today, this week and this month, for the ones `date` falls in
+fn add_to_periods(periods: [&mut Totals; 3], date: NaiveDate, today: NaiveDate, counted: Totals) {
+    let [day, week, month] = periods;
+
+    if date == today {
+        day.add(counted);
+    }
+    if date.iso_week() == today.iso_week() {
+        week.add(counted);
+    }
+    if date.year() == today.year() && date.month() == today.month() {
+        month.add(counted);
+    }
+}
+
+fn longest_streak(days: &BTreeSet<NaiveDate>) -> u64 {
+    let mut longest = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for day in days {
+        streak = match previous {
+            Some(previous) if *day - previous == Duration::days(1) => streak + 1,
+            _ => 1,
+        };
+        longest = longest.max(streak);
+        previous = Some(*day);
+    }
+
+    longest
+}
+
+// a streak is still current if the last active day was today or yesterday
+fn current_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> u64 {
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+
+    let mut streak = 0;
+
+    while days.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+
+    streak
+}
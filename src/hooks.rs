@@ -0,0 +1,543 @@
+use crate::controller::Controller;
+use crate::duration;
+use crate::timer::{Interruption, Interruptions, Timer, TimerEvent, TimerState, TimerType};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// every event that runs hooks, as <event>.sh and the scripts in <event>.d/ in the hooks directory
+pub const EVENTS: [&str; 13] = [
+    "before-start",
+    "before-break",
+    "start",
+    "resume",
+    "pause",
+    "adjust",
+    "interrupt",
+    "skip",
+    "stop",
+    "finish",
+    "cycle-complete",
+    "suspend",
+    "wake",
+];
+
+// how often a running hook is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// how long to keep reading the output of a hook that has exited. Processes it started in the
+// background may keep its stdout and stderr open for much longer.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+// the [hooks] table of the config file
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HookConfig {
+    // hooks that run longer are killed
+    #[serde(with = "duration::secs")]
+    pub timeout: Duration,
+    // the kinds defined in the config file, which hook names can filter on as well
+    #[serde(skip)]
+    pub kinds: Vec<String>,
+}
+
+impl Default for HookConfig {
+    fn default() -> HookConfig {
+        HookConfig {
+            timeout: Duration::from_secs(10),
+            kinds: Vec::new(),
+        }
+    }
+}
+
+// what a hook gets on stdin, most of it is also passed in environment variables
+#[derive(Serialize, Clone, Debug)]
+pub struct Payload {
+    pub event: String,
+    pub session: String,
+    pub timer_type: TimerType,
+    pub state: TimerState,
+    pub duration_secs: u64,
+    pub remaining_secs: u64,
+    pub elapsed_secs: u64,
+    pub cycle: u64,
+    pub long_break: bool,
+    pub kind: Option<String>,
+    pub label: Option<String>,
+    pub interruptions: Interruptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interruption: Option<Interruption>,
+    // how long the system was suspended, for the suspend and wake events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspended_secs: Option<u64>,
+}
+
+impl Payload {
+    pub fn new(event: &str, session: &str, timer: &Timer) -> Payload {
+        let duration = timer.duration();
+        let remaining = timer.time_left();
+
+        Payload {
+            event: event.to_string(),
+            session: session.to_string(),
+            timer_type: timer.timer_type(),
+            state: timer.state(),
+            duration_secs: duration.as_secs(),
+            remaining_secs: remaining.as_secs(),
+            elapsed_secs: duration.saturating_sub(remaining).as_secs(),
+            cycle: timer.cycle(),
+            long_break: timer.is_long_break(),
+            kind: timer.kind().map(String::from),
+            label: timer.label().map(String::from),
+            interruptions: timer.interruptions(),
+            interruption: match event {
+                "interrupt" => timer.last_interruption().cloned(),
+                _ => None,
+            },
+            suspended_secs: match event {
+                "suspend" | "wake" => timer.last_suspend().map(|gap| gap.as_secs()),
+                _ => None,
+            },
+        }
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("POMO_EVENT", self.event.clone()),
+            ("POMO_SESSION", self.session.clone()),
+            ("POMO_TIMER_TYPE", self.timer_type.to_string()),
+            // the name older hooks know it by
+            ("TIMER_TYPE", self.timer_type.to_string()),
+            ("POMO_STATE", self.state.to_string()),
+            ("POMO_DURATION", self.duration_secs.to_string()),
+            ("POMO_REMAINING", self.remaining_secs.to_string()),
+            ("POMO_ELAPSED", self.elapsed_secs.to_string()),
+            ("POMO_CYCLE", self.cycle.to_string()),
+            ("POMO_LONG_BREAK", self.long_break.to_string()),
+            // the kind from the config file, or the timer type
+            (
+                "POMO_KIND",
+                self.kind
+                    .clone()
+                    .unwrap_or_else(|| self.timer_type.to_string()),
+            ),
+            ("POMO_LABEL", self.label.clone().unwrap_or_default()),
+            (
+                "POMO_INTERNAL_INTERRUPTIONS",
+                self.interruptions.internal.to_string(),
+            ),
+            (
+                "POMO_EXTERNAL_INTERRUPTIONS",
+                self.interruptions.external.to_string(),
+            ),
+        ];
+
+        if let Some(interruption) = &self.interruption {
+            env.push(("POMO_INTERRUPTION", interruption.kind.to_string()));
+            env.push(("POMO_NOTE", interruption.note.clone().unwrap_or_default()));
+        }
+
+        if let Some(suspended_secs) = self.suspended_secs {
+            env.push(("POMO_SUSPENDED", suspended_secs.to_string()));
+        }
+
+        env
+    }
+}
+
+// how a hook run ended, a hook that timed out has no exit status
+pub struct Outcome {
+    pub hook: PathBuf,
+    // the hook's path in the hooks directory, e.g. "finish.d/10-slack.sh"
+    pub name: String,
+    pub status: Option<ExitStatus>,
+    pub elapsed: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Outcome {
+    pub fn success(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+
+    // why a before hook cancelled the timer
+    pub fn reason(&self) -> String {
+        match self.stderr.lines().rfind(|line| !line.trim().is_empty()) {
+            Some(line) => line.trim().to_string(),
+            None => self.to_string(),
+        }
+    }
+
+    fn log_message(&self) -> String {
+        let mut message = self.to_string();
+
+        for (name, output) in [("stdout", &self.stdout), ("stderr", &self.stderr)] {
+            for line in output.lines() {
+                message.push_str(&format!("\n  {}: {}", name, line));
+            }
+        }
+
+        message
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            Some(status) => write!(
+                f,
+                "{} exited with {} after {:.2}s",
+                self.name,
+                status,
+                self.elapsed.as_secs_f64()
+            ),
+            None => write!(
+                f,
+                "{} timed out after {:.2}s and was killed",
+                self.name,
+                self.elapsed.as_secs_f64()
+            ),
+        }
+    }
+}
+
+pub struct Hooks {
+    dir: PathBuf,
+    config: HookConfig,
+    log: PathBuf,
+    // hooks run one at a time, in the order of the events
+    queue: flume::Sender<Payload>,
+}
+
+impl Hooks {
+    pub fn new(dir: PathBuf, config: HookConfig, log: PathBuf) -> Arc<Hooks> {
+        let (queue, rx) = flume::unbounded::<Payload>();
+
+        let hooks = Arc::new(Hooks {
+            dir,
+            config,
+            log,
+            queue,
+        });
+
+        // the worker holds on weakly, so it stops once the hooks are dropped
+        let worker = Arc::downgrade(&hooks);
+
+        thread::spawn(move || {
+            while let Ok(payload) = rx.recv() {
+                let Some(hooks) = worker.upgrade() else {
+                    return;
+                };
+
+                hooks.run_logged(&payload);
+            }
+        });
+
+        hooks
+    }
+
+    // run the hooks for every event of the session's timers
+    pub fn watch(self: &Arc<Self>, session: &str, controller: &Arc<Mutex<Controller>>) {
+        for event in TimerEvent::ALL {
+            let hooks = Arc::clone(self);
+            let session = session.to_string();
+
+            Controller::on(
+                controller,
+                event,
+                Arc::new(move |timer: &Timer| {
+                    hooks.enqueue(Payload::new(&event.to_string(), &session, timer));
+
+                    // a long break means a full cycle of work timers is done
+                    if event == TimerEvent::Start && timer.is_long_break() {
+                        hooks.enqueue(Payload::new("cycle-complete", &session, timer));
+                    }
+                }),
+            );
+        }
+    }
+
+    // run the before hooks ahead of every new timer of the session, see `check`
+    pub fn guard(self: &Arc<Self>, session: &str, controller: &Arc<Mutex<Controller>>) {
+        let hooks = Arc::clone(self);
+        let session = session.to_string();
+
+        Controller::before_start(
+            controller,
+            Arc::new(move |timer: &mut Timer| hooks.check(&session, timer)),
+        );
+    }
+
+    // Runs the before-start hooks for a work timer that is about to start, or the before-break
+    // hooks for a break, and waits for them. A hook that fails cancels the timer, the reason is
+    // the last line it wrote to stderr. Lines such as "duration=10m" and "label=deploy" on stdout
+    // change the timer, and the hooks after it see the changes.
+    pub fn check(&self, session: &str, timer: &mut Timer) -> Result<(), String> {
+        let event = before_event(timer.timer_type());
+
+        for path in self.scripts(event, timer.timer_type(), timer.kind()) {
+            let payload = Payload::new(event, session, timer);
+            let name = self.name(&path);
+
+            let outcome = match self.run(&path, &payload) {
+                Ok(outcome) => outcome,
+                Err(err) => {
+                    let message = format!("Failed to run {}: {}", name, err);
+                    self.log(session, &message);
+                    return Err(message);
+                }
+            };
+
+            self.log(session, &outcome.log_message());
+
+            if !outcome.success() {
+                return Err(outcome.reason());
+            }
+
+            for warning in apply_overrides(timer, &outcome.stdout) {
+                self.log(session, &format!("{}: {}", name, warning));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn log(&self, session: &str, message: &str) {
+        if let Err(err) = append_log(&self.log, session, message) {
+            eprintln!("Failed to write hook log: {}", err);
+        }
+    }
+
+    fn enqueue(&self, payload: Payload) {
+        let _ = self.queue.send(payload);
+    }
+
+    // the hooks for an event: <event>.sh, then the executables in <event>.d/ in lexical order,
+    // leaving out scripts for other types and kinds of timers. Resume falls back to the start hooks,
+    // which used to run on resume as well.
+    pub fn scripts(&self, event: &str, timer_type: TimerType, kind: Option<&str>) -> Vec<PathBuf> {
+        let mut scripts = Vec::new();
+
+        let path = self.dir.join(format!("{}.sh", event));
+        if path.exists() {
+            scripts.push(path);
+        }
+
+        // a missing or unreadable directory has no scripts
+        if let Ok(entries) = fs::read_dir(self.dir.join(format!("{}.d", event))) {
+            let mut paths = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| is_script(path))
+                .collect::<Vec<_>>();
+
+            paths.sort();
+            scripts.extend(paths);
+        }
+
+        if scripts.is_empty() && event == "resume" {
+            return self.scripts("start", timer_type, kind);
+        }
+
+        scripts
+            .into_iter()
+            .filter(|path| runs_for(path, timer_type, kind, &self.config.kinds))
+            .collect()
+    }
+
+    // runs one hook for the payload's event and waits for it
+    pub fn run(&self, path: &Path, payload: &Payload) -> io::Result<Outcome> {
+        let input = serde_json::to_vec(payload)?;
+        let started_at = Instant::now();
+
+        let mut child = std::process::Command::new(path)
+            .args([payload.event.as_str(), &payload.timer_type.to_string()])
+            .envs(payload.env())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()?;
+
+        // read the output while the hook runs, so it doesn't block on a full pipe
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        // in the background as well, so a hook that doesn't read its stdin can't block pomo. A
+        // hook that exits without reading it makes this fail, which is fine.
+        if let Some(mut stdin) = child.stdin.take() {
+            thread::spawn(move || {
+                let _ = stdin.write_all(&input);
+            });
+        }
+
+        let status = wait_with_timeout(&mut child, self.config.timeout)?;
+        let deadline = Instant::now() + DRAIN_TIMEOUT;
+
+        Ok(Outcome {
+            hook: path.to_path_buf(),
+            name: self.name(path),
+            status,
+            elapsed: started_at.elapsed(),
+            stdout: read_until(&stdout, deadline),
+            stderr: read_until(&stderr, deadline),
+        })
+    }
+
+    pub fn name(&self, path: &Path) -> String {
+        path.strip_prefix(&self.dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    // a hook that fails or times out doesn't keep the others from running
+    fn run_logged(&self, payload: &Payload) {
+        for path in self.scripts(&payload.event, payload.timer_type, payload.kind.as_deref()) {
+            let message = match self.run(&path, payload) {
+                Ok(outcome) => outcome.log_message(),
+                Err(err) => format!("Failed to run {}: {}", self.name(&path), err),
+            };
+
+            self.log(&payload.session, &message);
+        }
+    }
+}
+
+pub fn before_event(timer_type: TimerType) -> &'static str {
+    match timer_type {
+        TimerType::Work => "before-start",
+        TimerType::Break | TimerType::LongBreak => "before-break",
+    }
+}
+
+// applies the "duration=..." and "label=..." lines a before hook printed, other lines are left
+// alone so hooks can still print what they are doing. Returns what couldn't be applied.
+pub fn apply_overrides(timer: &mut Timer, stdout: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for line in stdout.lines() {
+        match line.split_once('=') {
+            Some(("duration", value)) => match duration::parse(value) {
+                Ok(duration) => timer.set_duration(duration),
+                Err(err) => warnings.push(err.to_string()),
+            },
+            Some(("label", "")) => timer.set_label(None),
+            Some(("label", value)) => timer.set_label(Some(value.to_string())),
+            _ => {}
+        }
+    }
+
+    warnings
+}
+
+// executable files, leaving out hidden ones such as editor swap files
+fn is_script(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+
+    let executable = fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0);
+
+    !hidden && executable
+}
+
+// "10-slack.work.sh" only runs for work timers, "music.break" for short and long breaks,
+// "stretch.long_break" for long breaks and "standup.meeting.sh" for timers of the meeting kind
+fn runs_for(path: &Path, timer_type: TimerType, kind: Option<&str>, kinds: &[String]) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    let filters = name
+        .split('.')
+        .skip(1)
+        .filter(|part| {
+            TimerType::ALL.iter().any(|t| t.to_string() == *part) || kinds.iter().any(|k| k == part)
+        })
+        .collect::<Vec<_>>();
+
+    filters.is_empty()
+        || filters.into_iter().any(|filter| match filter {
+            "work" => timer_type == TimerType::Work,
+            "break" => timer_type.is_break(),
+            "long_break" => timer_type == TimerType::LongBreak,
+            filter => kind == Some(filter),
+        })
+}
+
+// sends what is read from the pipe until it is closed
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> flume::Receiver<Vec<u8>> {
+    let (tx, rx) = flume::unbounded();
+
+    if let Some(mut pipe) = pipe {
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+
+            while let Ok(read) = pipe.read(&mut buffer) {
+                if read == 0 || tx.send(buffer[..read].to_vec()).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    rx
+}
+
+// the output read until the pipe was closed or the deadline passed, whichever comes first
+fn read_until(output: &flume::Receiver<Vec<u8>>, deadline: Instant) -> String {
+    let mut bytes = Vec::new();
+
+    while let Ok(chunk) = output.recv_deadline(deadline) {
+        bytes.extend(chunk);
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// kills the child once the timeout has passed, returning None
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+
+        if Instant::now() >= deadline {
+            // the whole process group, so processes started by the hook don't keep its output open
+            unsafe {
+                libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+            }
+            child.wait()?;
+            return Ok(None);
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn append_log(path: &Path, session: &str, message: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    writeln!(
+        file,
+        "{} {} {}",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        session,
+        message
+    )
+}
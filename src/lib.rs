@@ -1,2 +1,21 @@
+pub mod alerts;
+pub mod client;
+pub mod config;
 pub mod controller;
+pub mod daemon;
+pub mod duration;
+pub mod history;
+pub mod hooks;
+#[cfg(feature = "notifications")]
+pub mod notifications;
+pub mod paths;
+pub mod peer;
+pub mod protocol;
+pub mod scheduler;
+pub mod sequence;
+pub mod state;
+pub mod status;
+pub mod suspend;
+pub mod tasks;
 pub mod timer;
+pub mod ui;
@@ -1,21 +1,45 @@
 use argh::FromArgs;
-use dirs;
-use inquire::Confirm;
-use pomo_cli::controller::Controller;
-use pomo_cli::timer::{Timer, TimerEvent, TimerType};
-use std::io::prelude::*;
-use std::os::unix::net::{UnixListener, UnixStream};
-use std::path::Path;
-use std::sync::Arc;
+use chrono::{Local, Utc};
+use inquire::{Confirm, InquireError, Select};
+use pomo_cli::alerts::{Alerter, AudioBackend, Backend, CommandBackend, NullBackend};
+use pomo_cli::client::{Client, ClientError};
+use pomo_cli::config::{self, ConfigFile, Settings};
+use pomo_cli::controller::{Config, Controller, Snapshot};
+use pomo_cli::daemon::{self, Daemon, DEFAULT_SESSION};
+use pomo_cli::duration;
+use pomo_cli::history::{self, Entry, History};
+use pomo_cli::hooks::{self, Hooks, Payload};
+#[cfg(feature = "notifications")]
+use pomo_cli::notifications::Notifier;
+use pomo_cli::paths;
+use pomo_cli::protocol::{Command, ErrorCode, EventKind, Reply};
+use pomo_cli::sequence::{Sequence, SequenceError};
+use pomo_cli::state::{self, DowntimePolicy};
+use pomo_cli::status::{self, Template};
+use pomo_cli::suspend::SuspendPolicy;
+use pomo_cli::tasks;
+use pomo_cli::timer::{
+    Adjustment, Interruption, InterruptionKind, Timer, TimerEvent, TimerSnapshot, TimerState,
+    TimerType,
+};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::task;
 
-const SOCKET_PATH: &str = "/tmp/pomo.sock";
-const HOOKS_PATH: &str = ".config/pomo/hooks";
+const DAEMON_ENV: &str = "POMO_DAEMON";
 
 #[derive(FromArgs)]
 /// A simple pomodoro timer
 struct Args {
+    #[argh(option)]
+    /// path of the control socket, defaults to $POMO_SOCKET or $XDG_RUNTIME_DIR/pomo/pomo.sock
+    socket: Option<String>,
     #[argh(subcommand)]
     subcommand: SubCommands,
 }
@@ -29,6 +53,19 @@ enum SubCommands {
     Stop(Stop),
     Status(Status),
     Next(Next),
+    Add(Add),
+    Sub(Sub),
+    Set(Set),
+    Label(Label),
+    Task(Task),
+    Interrupt(Interrupt),
+    Hooks(HooksArgs),
+    Log(Log),
+    Stats(Stats),
+    Attach(Attach),
+    List(List),
+    Ui(Ui),
+    Watch(Watch),
 }
 
 #[derive(FromArgs)]
@@ -38,281 +75,1489 @@ struct Start {
     #[argh(switch, short = 'a')]
     /// whether to automatically start the next timer when done
     auto: bool,
-    #[argh(option, short = 'd', default = "25")]
-    /// length of work period in minutes
-    duration: u64,
-    #[argh(option, short = 'b', default = "5")]
-    /// length of break period in minutes
-    break_duration: u64,
-    #[argh(option, default = "4")]
-    /// do a long break every nth time, set to 0 to never do a long break
-    long_break_interval: u64,
-    #[argh(option, default = "15")]
-    /// length of long break in minutes
-    long_break_duration: u64,
+    #[argh(switch)]
+    /// wait for 'pomo next' when a timer is done, even if auto mode is configured
+    no_auto: bool,
+    #[argh(switch, short = 'D')]
+    /// run in the background, detached from the terminal
+    daemon: bool,
+    #[argh(option)]
+    /// name of the timer, to run several timers side by side
+    name: Option<String>,
+    #[argh(option, short = 't')]
+    /// what the timer is spent on, e.g. "PR review #123"
+    task: Option<String>,
+    #[argh(option, short = 'p')]
+    /// name of a profile from the config file to use
+    profile: Option<String>,
+    #[argh(switch)]
+    /// continue the timer that was running when pomo last exited
+    restore: bool,
+    #[argh(switch)]
+    /// show desktop notifications, even if they are turned off in the config file
+    notifications: bool,
+    #[argh(switch)]
+    /// don't show desktop notifications
+    no_notifications: bool,
+    #[argh(option, default = "DowntimePolicy::Count")]
+    /// whether the time pomo wasn't running counts towards a restored timer, "count" or "discard"
+    downtime: DowntimePolicy,
+    #[argh(option, short = 'd', from_str_fn(parse_duration))]
+    /// length of work period, e.g. 25m, 90s or 1h15m (default 25m)
+    duration: Option<Duration>,
+    #[argh(option, short = 'b', from_str_fn(parse_duration))]
+    /// length of break period (default 5m)
+    break_duration: Option<Duration>,
+    #[argh(option)]
+    /// do a long break every nth time, set to 0 to never do a long break (default 4)
+    long_break_interval: Option<u64>,
+    #[argh(option, from_str_fn(parse_duration))]
+    /// length of long break (default 15m)
+    long_break_duration: Option<Duration>,
+    #[argh(switch)]
+    /// count skipped work timers towards the long break
+    count_skipped: bool,
+    #[argh(switch)]
+    /// don't count skipped work timers towards the long break
+    no_count_skipped: bool,
+    #[argh(option)]
+    /// what a running timer does when the computer was suspended, "count" the time as elapsed,
+    /// "pause" or "finish" (default count)
+    suspend: Option<SuspendPolicy>,
+    #[argh(option, from_str_fn(parse_sequence))]
+    /// timers to go through instead of the work and break durations, e.g. "work 50m, break 10m,
+    /// work 50m, long 30m", end with ", stop" to stop after the last one
+    sequence: Option<Sequence>,
 }
 
 #[derive(FromArgs)]
 /// Pause a running timer
 #[argh(subcommand, name = "pause")]
-struct Pause {}
+struct Pause {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
 
 #[derive(FromArgs)]
 /// Resume a paused timer
 #[argh(subcommand, name = "resume")]
-struct Resume {}
+struct Resume {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
 
 #[derive(FromArgs)]
 /// Stop the currently running timer
 #[argh(subcommand, name = "stop")]
-struct Stop {}
+struct Stop {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
 
 #[derive(FromArgs)]
 /// Get the status of the currently running timer
 #[argh(subcommand, name = "status")]
-struct Status {}
+struct Status {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+    #[argh(
+        option,
+        short = 'f',
+        default = "String::from(status::DEFAULT_TEMPLATE)"
+    )]
+    /// output format, either "json" or a template such as "{{prefix}} {{remaining}}"
+    format: String,
+}
 
 #[derive(FromArgs)]
 /// Skip to the next timer
 #[argh(subcommand, name = "next")]
-struct Next {}
+struct Next {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Add time to the current timer
+#[argh(subcommand, name = "add")]
+struct Add {
+    #[argh(positional, from_str_fn(parse_duration))]
+    /// how much time to add, e.g. 5m, 90s or 1h15m
+    duration: Duration,
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Take time off the current timer, it finishes if no time is left
+#[argh(subcommand, name = "sub")]
+struct Sub {
+    #[argh(positional, from_str_fn(parse_duration))]
+    /// how much time to take off, e.g. 2m or 30s
+    duration: Duration,
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Set the time left on the current timer
+#[argh(subcommand, name = "set")]
+struct Set {
+    #[argh(positional, from_str_fn(parse_duration))]
+    /// how much time is left, e.g. 10m or 1:30:00
+    duration: Duration,
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Set what the current timer is spent on, or remove the label if none is given
+#[argh(subcommand, name = "label")]
+struct Label {
+    #[argh(positional)]
+    /// the label, e.g. "PR review #123"
+    label: Option<String>,
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Count an interruption of the current work timer
+#[argh(subcommand, name = "interrupt")]
+struct Interrupt {
+    #[argh(positional)]
+    /// what the interruption was about
+    note: Option<String>,
+    #[argh(switch, short = 'e')]
+    /// the interruption came from someone else rather than yourself
+    external: bool,
+    #[argh(switch, short = 'p')]
+    /// also pause the timer
+    pause: bool,
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Work with the hook scripts in the hooks directory
+#[argh(subcommand, name = "hooks")]
+struct HooksArgs {
+    #[argh(subcommand)]
+    command: HooksCommands,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum HooksCommands {
+    Test(HooksTest),
+}
+
+#[derive(FromArgs)]
+/// Run the hooks for an event with made-up timer details and show their output
+#[argh(subcommand, name = "test")]
+struct HooksTest {
+    #[argh(positional)]
+    /// the event, e.g. start, finish or cycle-complete
+    event: String,
+    #[argh(switch, short = 'b', long = "break")]
+    /// pretend the timer is a break rather than a work timer
+    is_break: bool,
+    #[argh(option)]
+    /// pretend the timer is of a kind from the config file, e.g. meeting
+    kind: Option<String>,
+    #[argh(option)]
+    /// name of the timer to pass to the hook
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Manage the task list, finished work timers are credited to the active task
+#[argh(subcommand, name = "task")]
+struct Task {
+    #[argh(subcommand)]
+    command: TaskCommands,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum TaskCommands {
+    Add(TaskAdd),
+    List(TaskList),
+    Next(TaskNext),
+}
+
+#[derive(FromArgs)]
+/// Add a task to the end of the list
+#[argh(subcommand, name = "add")]
+struct TaskAdd {
+    #[argh(positional)]
+    /// what the task is about, e.g. "write RFC"
+    title: String,
+    #[argh(option, short = 'e')]
+    /// how many pomodoros the task will take
+    estimate: Option<u64>,
+}
+
+#[derive(FromArgs)]
+/// Show the tasks with their estimated and actual pomodoros
+#[argh(subcommand, name = "list")]
+struct TaskList {}
+
+#[derive(FromArgs)]
+/// Mark the active task as done and move on to the next one
+#[argh(subcommand, name = "next")]
+struct TaskNext {
+    #[argh(option)]
+    /// name of the timer to label with the next task
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Show the running timer and ask to start the next one when it finishes
+#[argh(subcommand, name = "attach")]
+struct Attach {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Show the most recent timer events
+#[argh(subcommand, name = "log")]
+struct Log {
+    #[argh(option)]
+    /// only show events of the timer with this name
+    name: Option<String>,
+    #[argh(option, short = 't')]
+    /// only show events of timers with this label
+    task: Option<String>,
+    #[argh(option, short = 'n', default = "20")]
+    /// number of events to show
+    count: usize,
+}
+
+#[derive(FromArgs)]
+/// Show totals, streaks and completion statistics
+#[argh(subcommand, name = "stats")]
+struct Stats {
+    #[argh(option)]
+    /// only count timers with this name
+    name: Option<String>,
+    #[argh(option, short = 't')]
+    /// only count timers with this label
+    task: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Show the running timer in a full-screen terminal UI
+#[argh(subcommand, name = "ui")]
+struct Ui {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+}
+
+#[derive(FromArgs)]
+/// Print the status whenever the timer changes, until it is stopped
+#[argh(subcommand, name = "watch")]
+struct Watch {
+    #[argh(option)]
+    /// name of the timer
+    name: Option<String>,
+    #[argh(switch)]
+    /// watch all timers
+    all: bool,
+    #[argh(switch)]
+    /// also print the status every second
+    ticks: bool,
+    #[argh(
+        option,
+        short = 'f',
+        default = "String::from(status::DEFAULT_TEMPLATE)"
+    )]
+    /// output format, either "json" or a template such as "{{prefix}} {{remaining}}"
+    format: String,
+}
+
+#[derive(FromArgs)]
+/// List all running timers
+#[argh(subcommand, name = "list")]
+struct List {}
 
 #[tokio::main]
 async fn main() {
     let args: Args = argh::from_env();
 
+    // passed on through the environment so a background pomo uses the same socket
+    if let Some(socket) = &args.socket {
+        std::env::set_var("POMO_SOCKET", socket);
+    }
+
     match args.subcommand {
         SubCommands::Start(args) => {
             start(args).await;
         }
-        SubCommands::Pause(_) => pause(),
-        SubCommands::Resume(_) => resume(),
-        SubCommands::Stop(_) => stop(),
-        SubCommands::Status(_) => status(),
-        SubCommands::Next(_) => next(),
+        SubCommands::Pause(args) => pause(args),
+        SubCommands::Resume(args) => resume(args),
+        SubCommands::Stop(args) => stop(args),
+        SubCommands::Status(args) => status(args),
+        SubCommands::Next(args) => next(args),
+        SubCommands::Add(args) => adjust(args.name, Adjustment::Add, args.duration),
+        SubCommands::Sub(args) => adjust(args.name, Adjustment::Subtract, args.duration),
+        SubCommands::Set(args) => adjust(args.name, Adjustment::Set, args.duration),
+        SubCommands::Label(args) => label(args),
+        SubCommands::Task(args) => task(args),
+        SubCommands::Interrupt(args) => interrupt(args),
+        SubCommands::Hooks(args) => match args.command {
+            HooksCommands::Test(args) => test_hook(args),
+        },
+        SubCommands::Log(args) => log(args),
+        SubCommands::Stats(args) => stats(args),
+        SubCommands::Attach(args) => attach(args),
+        SubCommands::List(_) => list(),
+        SubCommands::Ui(args) => ui(args),
+        SubCommands::Watch(args) => watch(args),
     };
 }
 
 async fn start(args: Start) {
-    if std::path::Path::new(SOCKET_PATH).exists() {
-        let answer = inquire::Confirm::new("Pomo is already running or was not terminated properly. Do you want to start a new pomodoro?").with_default(false).prompt();
-
-        match answer {
-            Ok(true) => {
-                // if there is a running timer, send an abort message to it
-                if let Ok(mut stream) = UnixStream::connect(SOCKET_PATH) {
-                    stream
-                        .write_all(b"abort")
-                        .expect("Failed to write to socket");
-                    stream
-                        .shutdown(std::net::Shutdown::Write)
-                        .expect("Failed to shutdown socket");
-                }
+    let Start {
+        auto,
+        no_auto,
+        daemon,
+        name,
+        task,
+        profile,
+        restore,
+        notifications,
+        no_notifications,
+        downtime,
+        break_duration,
+        duration,
+        long_break_duration,
+        long_break_interval,
+        count_skipped,
+        no_count_skipped,
+        suspend,
+        sequence,
+    } = args;
+
+    let session = name.unwrap_or_else(|| DEFAULT_SESSION.to_string());
+
+    if let Err(err) = daemon::validate_session_name(&session) {
+        eprintln!("{}", err);
+        std::process::exit(exitcode::USAGE);
+    }
+
+    // flags given on the command line override the config file and environment
+    let cli_settings = Settings {
+        work_duration: duration,
+        break_duration,
+        long_break_interval,
+        long_break_duration,
+        count_skipped: toggle("count-skipped", count_skipped, no_count_skipped),
+        suspend,
+        auto: toggle("auto", auto, no_auto),
+        notifications: toggle("notifications", notifications, no_notifications),
+        sequence,
+        kinds: BTreeMap::new(),
+    };
 
-                cleanup();
+    let restored = if restore {
+        match state::load(&paths::state_file(&session)) {
+            Ok(Some(saved)) => Some(saved.restore(downtime, Utc::now())),
+            Ok(None) => {
+                eprintln!("There is no saved timer to restore.");
+                std::process::exit(exitcode::NOINPUT);
             }
-            Ok(false) => {
-                std::process::exit(exitcode::OK);
+            Err(err) => {
+                eprintln!("Failed to read saved state: {}", err);
+                std::process::exit(exitcode::DATAERR);
             }
-            _ => std::process::exit(exitcode::USAGE),
         }
+    } else {
+        None
+    };
+
+    // a new timer works on the active task unless told otherwise
+    let (label, task) = match (task, &restored) {
+        (None, None) => tasks::TaskList::load(&paths::tasks_file())
+            .ok()
+            .and_then(|task_list| {
+                let task = task_list.active()?;
+                Some((Some(task.title.clone()), Some(task.id)))
+            })
+            .unwrap_or_default(),
+        (label, _) => (label, None),
+    };
+
+    // create a new controller for running timers, a restored one keeps its saved config unless
+    // flags override it
+    let controller_config = match &restored {
+        Some(snapshot) => Ok(cli_settings.apply(snapshot.config.clone())),
+        None => config::resolve(profile.as_deref(), cli_settings),
+    };
+    let controller_config = match controller_config {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(exitcode::CONFIG);
+        }
+    };
+
+    match restored.as_ref().map(|snapshot| snapshot.timer.state) {
+        Some(TimerState::Paused) => {
+            println!("Restored a paused timer, use 'pomo resume' to continue it.");
+        }
+        Some(TimerState::Stopped) if !controller_config.auto => {
+            println!("The restored timer has finished, use 'pomo next' to start the next one.");
+        }
+        _ => {}
+    }
+
+    let socket_path = paths::socket_file();
+
+    // hand the new timer to the pomo that is already running
+    if let Ok(client) = Client::connect(&socket_path) {
+        start_in_daemon(
+            client.with_session(Some(session.clone())),
+            controller_config,
+            restored,
+            label,
+            task,
+        );
+
+        if daemon {
+            println!(
+                "Pomo is already running in the background, the timer '{}' was started there.",
+                session
+            );
+        }
+        return;
     }
 
-    // handle Ctrl+C
+    // report a broken config file or sound files before going into the background
+    let (alerter, hooks) = match load_alerts_and_hooks() {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(exitcode::CONFIG);
+        }
+    };
+
+    // a socket nobody listens on was left behind by a pomo that didn't exit cleanly
+    std::fs::remove_file(&socket_path).unwrap_or(());
+
+    // the detached process runs this same command again, marked by DAEMON_ENV, which hooks and
+    // players started from it shouldn't inherit
+    let detached = std::env::var_os(DAEMON_ENV).is_some();
+    std::env::remove_var(DAEMON_ENV);
+
+    if daemon && !detached {
+        spawn_daemon();
+        return;
+    }
+
+    if detached {
+        std::fs::create_dir_all(paths::state_dir()).expect("Failed to create state directory");
+        std::fs::write(paths::pid_file(), std::process::id().to_string())
+            .expect("Failed to write pidfile");
+    }
+
+    // handle Ctrl+C, and SIGTERM when running in the background
     ctrlc::set_handler(move || {
-        cleanup();
+        cleanup(detached);
         std::process::exit(exitcode::OK);
     })
     .expect("Error setting Ctrl-C handler");
 
-    let Start {
-        auto,
-        break_duration,
-        duration,
-        long_break_duration,
-        long_break_interval,
-    } = args;
+    // create a new socket listener
+    // a socket given with --socket or $POMO_SOCKET lives wherever the user put it
+    if socket_path.starts_with(paths::runtime_dir()) {
+        if let Err(err) = paths::ensure_private_dir(&paths::runtime_dir()) {
+            eprintln!("Failed to prepare socket directory: {}", err);
+            std::process::exit(exitcode::CANTCREAT);
+        }
+    }
 
-    // multiply the durations by 60 because they are in minutes
-    let duration = Duration::from_secs(duration * 60);
-    let break_duration = Duration::from_secs(break_duration * 60);
+    // only the current user may connect
+    let listener = paths::bind_private(&socket_path).expect("Failed to bind to socket");
 
-    // create a new socket listener
-    let listener = UnixListener::bind(SOCKET_PATH).expect("Failed to bind to socket");
+    // only the timer started from this terminal asks about the next timer here, others are
+    // started from elsewhere and use 'pomo attach'
+    let interactive_session = if detached {
+        None
+    } else {
+        Some(session.clone())
+    };
 
-    // create a new controller for running timers
-    let controller_config = pomo_cli::controller::Config {
-        auto,
-        break_duration,
-        long_break_duration: Duration::from_secs(long_break_duration),
-        long_break_interval,
-        work_duration: duration,
+    #[cfg(feature = "notifications")]
+    let notifier = match Notifier::connect().await {
+        Ok(notifier) => Some(notifier),
+        Err(err) => {
+            if controller_config.notifications {
+                eprintln!("Desktop notifications are unavailable: {}", err);
+            }
+            None
+        }
     };
 
-    let controller = Controller::new(controller_config);
+    let guard_hooks = Arc::clone(&hooks);
+    let guard_session = Arc::new(move |name: &str, controller: &Arc<Mutex<Controller>>| {
+        guard_hooks.guard(name, controller);
+    });
 
-    let on_timer_finished = move |timer: &Timer| {
-        run_hook("finish.sh", timer.timer_type());
+    let (daemon, shutdown_rx) = Daemon::new(
+        guard_session,
+        Arc::new(move |name, controller| {
+            let interactive = interactive_session.as_deref() == Some(name);
+            setup_session(name, controller, interactive, detached);
+            hooks.watch(name, controller);
+            alerter.watch(controller);
 
-        if auto {
-            return;
+            #[cfg(feature = "notifications")]
+            if let Some(notifier) = &notifier {
+                if Controller::config(controller).notifications {
+                    notifier.watch(name, controller);
+                }
+            }
+        }),
+    );
+
+    // the before hooks block, keep them off the runtime
+    let started = {
+        let daemon = Arc::clone(&daemon);
+        tokio::task::spawn_blocking(move || {
+            daemon.start_session(&session, controller_config, restored, label, task, false)
+        })
+        .await
+        .expect("Failed to start timer")
+    };
+
+    if let Err(err) = started {
+        eprintln!("{}", err);
+        cleanup(detached);
+        std::process::exit(exitcode::TEMPFAIL);
+    }
+
+    daemon.serve(listener);
+
+    shutdown_rx
+        .recv_async()
+        .await
+        .expect("Failed to run socket listener");
+
+    cleanup(detached);
+}
+
+// the value of a switch that can be turned on with --<name> and off with --no-<name>, if either
+// was given
+fn toggle(name: &str, on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, true) => {
+            eprintln!("Use either --{} or --no-{}, not both.", name, name);
+            std::process::exit(exitcode::USAGE);
         }
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    }
+}
 
-        // wait for user input
-        task::spawn_blocking(move || {
-            // println!("Press enter to start the next timer.");
-            // let _ = std::io::stdin().read_line(&mut String::new());
+fn load_alerts_and_hooks() -> Result<(Arc<Alerter>, Arc<Hooks>), String> {
+    let file = ConfigFile::load(&paths::config_file()).map_err(|err| err.to_string())?;
+    let kinds = file.kinds().into_keys().collect();
+    let ConfigFile {
+        alerts, mut hooks, ..
+    } = file;
+    hooks.kinds = kinds;
 
-            if let Ok(true) = Confirm::new("Start the next timer?")
-                .with_default(true)
-                .prompt()
-            {
-                write_socket_message("next");
-            }
-        });
+    let backend: Arc<dyn AudioBackend> = match alerts.backend {
+        Backend::Command => Arc::new(CommandBackend::new(alerts.player.clone())),
+        Backend::Null => Arc::new(NullBackend::default()),
     };
 
-    Controller::on(&controller, TimerEvent::Start, Arc::new(on_timer_started));
-    Controller::on(&controller, TimerEvent::Finish, Arc::new(on_timer_finished));
-    Controller::on(&controller, TimerEvent::Pause, Arc::new(on_timer_paused));
+    let alerter = Alerter::new(alerts, backend).map_err(|err| err.to_string())?;
+    let hooks = Hooks::new(paths::hooks_dir(), hooks, paths::hooks_log_file());
 
-    Controller::start(&controller);
+    Ok((Arc::new(alerter), hooks))
+}
 
-    // listen for incoming socket messages
-    let handle = tokio::task::spawn_blocking(move || {
-        for stream in listener.incoming() {
-            match stream {
-                Ok(mut stream) => {
-                    let mut incoming_string = String::new();
+fn start_in_daemon(
+    mut client: Client,
+    config: Config,
+    snapshot: Option<Snapshot>,
+    label: Option<String>,
+    task: Option<u64>,
+) {
+    let command = |replace| Command::Start {
+        config: config.clone(),
+        snapshot: snapshot.clone().map(Box::new),
+        replace,
+        label: label.clone(),
+        task,
+    };
 
-                    stream
-                        .read_to_string(&mut incoming_string)
-                        .expect("Failed to read from socket");
+    match client.request(command(false)) {
+        Ok(_) => {}
+        Err(ClientError::Daemon(error)) if error.code == ErrorCode::SessionExists => {
+            let answer = Confirm::new(&format!("{} Do you want to replace it?", error))
+                .with_default(false)
+                .prompt();
 
-                    match incoming_string.as_str() {
-                        "abort" => {
-                            // another instance started, abort this one
-                            return;
-                        }
-                        "pause" => {
-                            Controller::pause(&controller);
-                        }
-                        "resume" => {
-                            Controller::start(&controller);
-                        }
-                        "stop" => {
-                            Controller::stop(&controller);
+            match answer {
+                Ok(true) => {
+                    if let Err(err) = client.request(command(true)) {
+                        exit_with_error(err);
+                    }
+                }
+                Ok(false) => std::process::exit(exitcode::OK),
+                _ => std::process::exit(exitcode::USAGE),
+            }
+        }
+        Err(err) => exit_with_error(err),
+    }
+}
 
-                            cleanup();
-                            // end the program when stop is called
-                            return;
-                        }
-                        "next" => {
-                            Controller::next(&controller);
-                        }
-                        "status" => {
-                            let timer = Controller::get_current_timer(&controller);
-                            let timer = timer.lock().expect("Failed to lock timer");
-                            let time_left = timer.time_left();
-
-                            let prefix = match timer.timer_type() {
-                                TimerType::Work => "W",
-                                TimerType::Break => "B",
-                            };
-
-                            let minutes = time_left.as_secs() / 60;
-                            let seconds = time_left.as_secs() % 60;
-
-                            stream
-                                .write_all(
-                                    format!("{} {:02}:{:02}", prefix, minutes, seconds).as_bytes(),
-                                )
-                                .expect("Failed to write to socket");
+fn setup_session(
+    session: &str,
+    controller: &Arc<Mutex<Controller>>,
+    interactive: bool,
+    detached: bool,
+) {
+    let auto = Controller::config(controller).auto;
+
+    let on_timer_finished = {
+        let session = session.to_string();
+
+        move |timer: &Timer| {
+            // timers of a kind, such as meetings, aren't pomodoros
+            if timer.timer_type() == TimerType::Work && timer.kind().is_none() {
+                if let Some(task) = timer.task() {
+                    credit_task(task);
+                }
+            }
+
+            // there is nobody to ask in the background, use 'pomo attach' or 'pomo next' instead
+            if auto || !interactive {
+                return;
+            }
+
+            let session = session.clone();
+
+            // wait for user input
+            task::spawn_blocking(move || {
+                // println!("Press enter to start the next timer.");
+                // let _ = std::io::stdin().read_line(&mut String::new());
+
+                if let Ok(Some(commands)) = ask_next_timer() {
+                    // a before hook may refuse to start the next timer, that's no reason to exit
+                    for command in commands {
+                        if let Err(err) = connect(Some(session.clone())).request(command) {
+                            eprintln!("{}", err);
                         }
-                        _ => {}
                     }
                 }
+            });
+        }
+    };
+
+    Controller::on(controller, TimerEvent::Finish, Arc::new(on_timer_finished));
+
+    for event in TimerEvent::ALL {
+        let session = session.to_string();
+
+        // record every transition in the history log, and in the log file when in the background
+        Controller::on(
+            controller,
+            event,
+            Arc::new(move |timer: &Timer| {
+                if let Err(err) = History::default().append(&Entry::new(&session, event, timer)) {
+                    eprintln!("Failed to write history: {}", err);
+                }
 
-                Err(err) => {
-                    println!("Error: {:?}", err);
-                    break;
+                if detached {
+                    println!(
+                        "{} {} {} {}",
+                        Local::now().format("%Y-%m-%d %H:%M:%S"),
+                        session,
+                        event,
+                        timer.timer_type()
+                    );
                 }
-            }
+            }),
+        );
+    }
+}
+
+// a finished work timer counts towards the task it was spent on
+fn credit_task(task: u64) {
+    let path = paths::tasks_file();
+
+    let mut task_list = match tasks::TaskList::load(&path) {
+        Ok(task_list) => task_list,
+        Err(err) => {
+            eprintln!("Failed to read tasks: {}", err);
+            return;
         }
-    });
+    };
+
+    // the task may have been removed from the list in the meantime
+    if task_list.credit(task).is_none() {
+        return;
+    }
+
+    if let Err(err) = task_list.save(&path) {
+        eprintln!("Failed to save tasks: {}", err);
+    }
+}
 
-    handle.await.expect("Failed to run socket listener");
+enum NextTimer {
+    Task(tasks::Task),
+    WithoutTask,
+    NotNow,
 }
 
-fn on_timer_started(timer: &Timer) {
-    run_hook("start.sh", timer.timer_type());
+impl std::fmt::Display for NextTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NextTimer::Task(task) => write!(f, "{}", task),
+            NextTimer::WithoutTask => write!(f, "Without a task"),
+            NextTimer::NotNow => write!(f, "Not now"),
+        }
+    }
 }
 
-fn on_timer_paused(timer: &Timer) {
-    run_hook("pause.sh", timer.timer_type());
+// asks whether to start the next timer, and which task to spend it on when there are open tasks,
+// returns the commands to send or None to leave the finished timer as it is
+fn ask_next_timer() -> Result<Option<Vec<Command>>, InquireError> {
+    let path = paths::tasks_file();
+    let mut task_list = tasks::TaskList::load(&path).unwrap_or_default();
+
+    if task_list.open().next().is_none() {
+        let start = Confirm::new("Start the next timer?")
+            .with_default(true)
+            .prompt()?;
+
+        return Ok(start.then(|| vec![Command::Next]));
+    }
+
+    // the active task comes first, so enter keeps working on it
+    let active = task_list.active().map(|task| task.id);
+    let mut choices: Vec<NextTimer> = task_list.open().cloned().map(NextTimer::Task).collect();
+    choices
+        .sort_by_key(|choice| !matches!(choice, NextTimer::Task(task) if Some(task.id) == active));
+    choices.push(NextTimer::WithoutTask);
+    choices.push(NextTimer::NotNow);
+
+    let (label, task) = match Select::new("Start the next timer with:", choices).prompt()? {
+        NextTimer::Task(task) => {
+            task_list.activate(task.id);
+
+            if let Err(err) = task_list.save(&path) {
+                eprintln!("Failed to save tasks: {}", err);
+            }
+
+            (Some(task.title), Some(task.id))
+        }
+        NextTimer::WithoutTask => (None, None),
+        NextTimer::NotNow => return Ok(None),
+    };
+
+    Ok(Some(vec![Command::Label { label, task }, Command::Next]))
 }
 
-fn run_hook(hook_name: &str, timer_type: TimerType) {
-    let mut path = dirs::home_dir().expect("Failed to get home directory");
-    path.push(HOOKS_PATH);
-    path.push(Path::new(hook_name));
+fn spawn_daemon() {
+    let log_path = paths::log_file();
+
+    if let Some(parent) = log_path.parent() {
+        std::fs::create_dir_all(parent).expect("Failed to create log directory");
+    }
+
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .expect("Failed to open log file");
 
-    // we don't care if the hook doesn't exist
-    let _ = std::process::Command::new(path)
-        .env("TIMER_TYPE", timer_type.to_string())
-        .spawn();
+    let mut command =
+        std::process::Command::new(std::env::current_exe().expect("Failed to find pomo"));
+
+    command
+        .args(std::env::args_os().skip(1))
+        .env(DAEMON_ENV, "1")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone().expect("Failed to open log file"))
+        .stderr(log);
+
+    // start a new session so the daemon has no controlling terminal
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    let mut child = command
+        .spawn()
+        .expect("Failed to start pomo in the background");
+
+    // wait until the daemon accepts connections
+    for _ in 0..50 {
+        if UnixStream::connect(paths::socket_file()).is_ok() {
+            println!(
+                "Pomo is running in the background (pid {}), logging to {}.",
+                child.id(),
+                log_path.display()
+            );
+            return;
+        }
+
+        if let Ok(Some(status)) = child.try_wait() {
+            eprintln!(
+                "Pomo exited right away ({}), see {} for details.",
+                status,
+                log_path.display()
+            );
+            std::process::exit(exitcode::SOFTWARE);
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    eprintln!(
+        "Pomo did not start listening in time, see {} for details.",
+        log_path.display()
+    );
+    std::process::exit(exitcode::SOFTWARE);
 }
 
-fn cleanup() {
+fn cleanup(detached: bool) {
     // remove socket if it exists
-    std::fs::remove_file(SOCKET_PATH).unwrap_or(());
+    std::fs::remove_file(paths::socket_file()).unwrap_or(());
+
+    if detached {
+        std::fs::remove_file(paths::pid_file()).unwrap_or(());
+    }
 }
 
-fn write_socket_message(message: &str) -> UnixStream {
-    let mut stream = match UnixStream::connect(SOCKET_PATH) {
-        Ok(stream) => stream,
+fn connect(session: Option<String>) -> Client {
+    match Client::connect(&paths::socket_file()) {
+        Ok(client) => client.with_session(session),
         Err(_) => {
-            println!("Failed to connect to socket. Please start a timer using 'pomo start' first.");
+            eprintln!(
+                "Failed to connect to socket. Please start a timer using 'pomo start' first."
+            );
             std::process::exit(exitcode::SOFTWARE);
         }
+    }
+}
+
+fn exit_with_error(err: ClientError) -> ! {
+    eprintln!("{}", err);
+
+    let code = match err {
+        ClientError::Daemon(error) if error.code == ErrorCode::VersionMismatch => {
+            exitcode::PROTOCOL
+        }
+        ClientError::Daemon(error) if error.code == ErrorCode::SessionNotFound => {
+            exitcode::UNAVAILABLE
+        }
+        ClientError::Daemon(error) if error.code == ErrorCode::Cancelled => exitcode::TEMPFAIL,
+        ClientError::InvalidResponse => exitcode::PROTOCOL,
+        _ => exitcode::SOFTWARE,
     };
+    std::process::exit(code);
+}
 
-    stream
-        .write_all(message.as_bytes())
-        .expect("Failed to write to socket");
-    stream
-        .shutdown(std::net::Shutdown::Write)
-        .expect("Failed to shutdown socket");
-    stream
+fn send_command(session: Option<String>, command: Command) -> Reply {
+    match connect(session).request(command) {
+        Ok(reply) => reply,
+        Err(err) => exit_with_error(err),
+    }
 }
 
-fn pause() {
+fn pause(args: Pause) {
     // pause the currently running timer
-    write_socket_message("pause");
+    send_command(args.name, Command::Pause);
 }
 
-fn resume() {
+fn resume(args: Resume) {
     // resume the currently paused timer
-    write_socket_message("resume");
+    send_command(args.name, Command::Resume);
 }
 
-fn stop() {
+fn stop(args: Stop) {
     // stop the currently running timer
-    write_socket_message("stop");
+    send_command(args.name, Command::Stop);
 }
 
-fn status() {
-    // get the status of the currently running timer
-    // write to socket and listen for response
-    let mut stream = write_socket_message("status");
+// None stands for json
+fn parse_format(format: &str) -> Option<Template> {
+    if format == "json" {
+        return None;
+    }
 
-    let mut incoming_string = String::new();
-    stream
-        .read_to_string(&mut incoming_string)
-        .expect("Failed to read from socket");
+    match Template::parse(format) {
+        Ok(template) => Some(template),
+        Err(err) => {
+            eprintln!("Invalid format: {}", err);
+            std::process::exit(exitcode::USAGE);
+        }
+    }
+}
+
+fn status(args: Status) {
+    let template = parse_format(&args.format);
+
+    // get the status of the currently running timer
+    if let Reply::Status(status) = send_command(args.name, Command::Status) {
+        match template {
+            Some(template) => println!("{}", template.render(&status)),
+            None => println!(
+                "{}",
+                serde_json::to_string(&status).expect("Failed to serialize status")
+            ),
+        }
+    }
 }
 
-fn next() {
+fn next(args: Next) {
     // skip to the next timer
-    write_socket_message("next");
+    send_command(args.name, Command::Next);
+}
+
+fn adjust(session: Option<String>, adjustment: Adjustment, amount: Duration) {
+    // change the time left on the current timer
+    send_command(
+        session,
+        Command::Adjust {
+            adjustment,
+            duration: amount,
+        },
+    );
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    duration::parse(value).map_err(|err| err.to_string())
+}
+
+fn parse_sequence(value: &str) -> Result<Sequence, String> {
+    value.parse().map_err(|err: SequenceError| err.to_string())
+}
+
+fn label(args: Label) {
+    send_command(
+        args.name,
+        Command::Label {
+            label: args.label,
+            task: None,
+        },
+    );
+}
+
+fn interrupt(args: Interrupt) {
+    let kind = if args.external {
+        InterruptionKind::External
+    } else {
+        InterruptionKind::Internal
+    };
+
+    send_command(
+        args.name,
+        Command::Interrupt {
+            kind,
+            note: args.note,
+            pause: args.pause,
+        },
+    );
+}
+
+fn test_hook(args: HooksTest) {
+    if !hooks::EVENTS.contains(&args.event.as_str()) {
+        eprintln!(
+            "Unknown event '{}', hooks exist for: {}.",
+            args.event,
+            hooks::EVENTS.join(", ")
+        );
+        std::process::exit(exitcode::USAGE);
+    }
+
+    let file = match ConfigFile::load(&paths::config_file()) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(exitcode::CONFIG);
+        }
+    };
+
+    let kinds = file.kinds();
+
+    let kind = args.kind.map(|name| match kinds.get(&name) {
+        Some(kind) => (name, kind.timer_type),
+        None => {
+            eprintln!(
+                "Unknown kind '{}', define it in a [kinds.{}] table of the config file.",
+                name, name
+            );
+            std::process::exit(exitcode::USAGE);
+        }
+    });
+
+    let mut config = file.hooks;
+    config.kinds = kinds.into_keys().collect();
+
+    let hooks = Hooks::new(paths::hooks_dir(), config, paths::hooks_log_file());
+
+    let before = args.event.starts_with("before-");
+    let is_break = args.is_break
+        || args.event == "before-break"
+        || kind
+            .as_ref()
+            .is_some_and(|(_, timer_type)| timer_type.is_break());
+
+    // a paused timer two or five minutes in, a timer that is about to start, or the long break
+    // that follows a completed cycle
+    let (timer_type, duration, remaining) = match args.event.as_str() {
+        "cycle-complete" => (TimerType::LongBreak, 15 * 60, 15 * 60),
+        "start" | "before-start" | "before-break" if is_break => (TimerType::Break, 5 * 60, 5 * 60),
+        "start" | "before-start" => (TimerType::Work, 25 * 60, 25 * 60),
+        _ if is_break => (TimerType::Break, 5 * 60, 3 * 60),
+        _ => (TimerType::Work, 25 * 60, 20 * 60),
+    };
+
+    // a kind of long break stays one
+    let timer_type = match &kind {
+        Some((_, TimerType::LongBreak)) => TimerType::LongBreak,
+        _ => timer_type,
+    };
+
+    let timer = Timer::restore(&TimerSnapshot {
+        timer_type,
+        state: if before {
+            TimerState::Stopped
+        } else {
+            TimerState::Paused
+        },
+        duration: Duration::from_secs(duration),
+        remaining: Duration::from_secs(remaining),
+        kind: kind.map(|(name, _)| name),
+        label: Some("pomo hooks test".to_string()),
+        task: None,
+        interruptions: Vec::new(),
+    });
+
+    // a laptop that was closed for twenty minutes
+    if args.event == "suspend" || args.event == "wake" {
+        Timer::suspended(&timer, Duration::from_secs(20 * 60), SuspendPolicy::Count);
+    }
+
+    let mut timer = timer.lock().expect("Failed to lock timer");
+
+    if args.event == "interrupt" {
+        timer.interrupt(Interruption {
+            kind: InterruptionKind::Internal,
+            note: Some("pomo hooks test".to_string()),
+        });
+    }
+
+    // a break is checked by the before-break hooks, whichever was asked for
+    let event = match before {
+        true => hooks::before_event(timer_type),
+        false => args.event.as_str(),
+    };
+
+    let session = args.name.unwrap_or_else(|| DEFAULT_SESSION.to_string());
+    let scripts = hooks.scripts(event, timer_type, timer.kind());
+
+    if scripts.is_empty() {
+        eprintln!(
+            "There is no hook for '{}' {} timers, create {} or add scripts to {}.",
+            event,
+            timer_type,
+            paths::hooks_dir().join(format!("{}.sh", event)).display(),
+            paths::hooks_dir().join(format!("{}.d", event)).display()
+        );
+        std::process::exit(exitcode::NOINPUT);
+    }
+
+    // run them all like pomo does, even if one of them fails, but a failing before hook cancels
+    // the timer right away
+    let mut failed = false;
+
+    for path in scripts {
+        let payload = Payload::new(event, &session, &timer);
+
+        match hooks.run(&path, &payload) {
+            Ok(outcome) => {
+                println!("{}", outcome);
+                print!("{}", outcome.stdout);
+                eprint!("{}", outcome.stderr);
+
+                if before && !outcome.success() {
+                    eprintln!("The timer would not start: {}", outcome.reason());
+                    std::process::exit(exitcode::TEMPFAIL);
+                }
+
+                if before {
+                    for warning in hooks::apply_overrides(&mut timer, &outcome.stdout) {
+                        eprintln!("{}", warning);
+                    }
+                }
+
+                failed |= !outcome.success();
+            }
+            Err(err) => {
+                eprintln!("Failed to run {}: {}", hooks.name(&path), err);
+
+                if before {
+                    std::process::exit(exitcode::TEMPFAIL);
+                }
+
+                failed = true;
+            }
+        }
+    }
+
+    if before {
+        println!(
+            "The {} timer would start with {}{}.",
+            timer_type,
+            status::format_clock(timer.duration().as_secs()),
+            label_suffix(timer.label())
+        );
+    }
+
+    if failed {
+        std::process::exit(exitcode::SOFTWARE);
+    }
+}
+
+fn task(args: Task) {
+    let path = paths::tasks_file();
+
+    let mut task_list = match tasks::TaskList::load(&path) {
+        Ok(task_list) => task_list,
+        Err(err) => {
+            eprintln!("Failed to read tasks: {}", err);
+            std::process::exit(exitcode::DATAERR);
+        }
+    };
+
+    let save = |task_list: &tasks::TaskList| {
+        if let Err(err) = task_list.save(&path) {
+            eprintln!("Failed to save tasks: {}", err);
+            std::process::exit(exitcode::CANTCREAT);
+        }
+    };
+
+    match args.command {
+        TaskCommands::Add(args) => {
+            let task = task_list.add(&args.title, args.estimate).clone();
+            save(&task_list);
+
+            println!("Added task {}: {}", task.id, task.title);
+        }
+        TaskCommands::List(_) => {
+            if task_list.tasks().is_empty() {
+                println!("There are no tasks, add one with 'pomo task add'.");
+            }
+
+            let active = task_list.active().map(|task| task.id);
+
+            for task in task_list.tasks() {
+                let estimate = task
+                    .estimate
+                    .map_or("-".to_string(), |estimate| estimate.to_string());
+
+                println!(
+                    "{} {:>3}  {:>7}  {}{}",
+                    if Some(task.id) == active { "*" } else { " " },
+                    task.id,
+                    format!("{}/{}", task.pomodoros, estimate),
+                    task.title,
+                    if task.done { " (done)" } else { "" }
+                );
+            }
+        }
+        TaskCommands::Next(args) => {
+            let next = task_list.complete_active().cloned();
+            save(&task_list);
+
+            let (label, task) = match next {
+                Some(task) => (Some(task.title), Some(task.id)),
+                None => (None, None),
+            };
+
+            match &label {
+                Some(title) => println!("Working on: {}", title),
+                None => println!("All tasks are done."),
+            }
+
+            // the running timer moves on to the next task as well
+            if let Ok(client) = Client::connect(&paths::socket_file()) {
+                if let Err(err) = client
+                    .with_session(args.name)
+                    .request(Command::Label { label, task })
+                {
+                    exit_with_error(err);
+                }
+            }
+        }
+    }
+}
+
+fn ui(args: Ui) {
+    let client = connect(args.name.clone());
+
+    if let Err(err) = pomo_cli::ui::run(client, args.name) {
+        exit_with_error(err);
+    }
+}
+
+fn watch(args: Watch) {
+    let template = parse_format(&args.format);
+
+    let events = match connect(args.name).subscribe(args.ticks, args.all) {
+        Ok(events) => events,
+        Err(err) => exit_with_error(err),
+    };
+
+    for event in events {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => exit_with_error(err),
+        };
+
+        match &template {
+            Some(template) => println!("{}", template.render(&event.status)),
+            None => println!(
+                "{}",
+                serde_json::to_string(&event).expect("Failed to serialize event")
+            ),
+        }
+    }
+}
+
+fn list() {
+    let template = Template::parse(status::DEFAULT_TEMPLATE).expect("Invalid status template");
+
+    if let Reply::Sessions { sessions } = send_command(None, Command::List) {
+        for session in sessions {
+            println!(
+                "{:<16}{}{}{}",
+                session.name,
+                template.render(&session.status),
+                state_suffix(session.status.state),
+                label_suffix(session.status.label.as_deref())
+            );
+        }
+    }
+}
+
+fn state_suffix(state: TimerState) -> &'static str {
+    match state {
+        TimerState::Paused => " (paused)",
+        _ => "",
+    }
+}
+
+// e.g. " · PR review #123"
+fn label_suffix(label: Option<&str>) -> String {
+    label
+        .map(|label| format!(" · {}", label))
+        .unwrap_or_default()
+}
+
+fn log(args: Log) {
+    let entries = match history_entries(args.name.as_deref(), args.task.as_deref()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Failed to read history: {}", err);
+            std::process::exit(exitcode::IOERR);
+        }
+    };
+
+    let skip = entries.len().saturating_sub(args.count);
+
+    for entry in &entries[skip..] {
+        println!(
+            "{}  {:<9}  {:<10}  {}/{}{}{}{}",
+            entry.time.format("%Y-%m-%d %H:%M"),
+            entry.event.to_string(),
+            entry
+                .kind
+                .clone()
+                .unwrap_or_else(|| entry.timer_type.to_string()),
+            status::format_clock(entry.elapsed_secs),
+            status::format_clock(entry.duration_secs),
+            label_suffix(entry.label.as_deref()),
+            interruption_suffix(entry.interruption.as_ref()),
+            suspend_suffix(entry.suspended_secs),
+        );
+    }
+}
+
+// e.g. " (suspended for 20:00)"
+fn suspend_suffix(suspended_secs: Option<u64>) -> String {
+    suspended_secs
+        .map(|secs| format!(" (suspended for {})", status::format_clock(secs)))
+        .unwrap_or_default()
+}
+
+// e.g. " (external: phone call)"
+fn interruption_suffix(interruption: Option<&Interruption>) -> String {
+    match interruption {
+        Some(Interruption {
+            kind,
+            note: Some(note),
+        }) => format!(" ({}: {})", kind, note),
+        Some(Interruption { kind, note: None }) => format!(" ({})", kind),
+        None => String::new(),
+    }
+}
+
+fn stats(args: Stats) {
+    let entries = match history_entries(args.name.as_deref(), args.task.as_deref()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Failed to read history: {}", err);
+            std::process::exit(exitcode::IOERR);
+        }
+    };
+
+    let stats = history::Stats::compute(&entries, chrono::Local::now().date_naive());
+
+    for (name, totals) in [
+        ("Today", stats.today),
+        ("This week", stats.week),
+        ("This month", stats.month),
+    ] {
+        println!(
+            "{:<12}{} pomodoros, {} focus, {} interruptions",
+            format!("{}:", name),
+            totals.pomodoros,
+            format_hours(totals.focus_secs),
+            totals.interruptions
+        );
+    }
+
+    println!(
+        "{:<12}{} days (longest {})",
+        "Streak:", stats.current_streak, stats.longest_streak
+    );
+
+    match stats.completion_ratio() {
+        Some(ratio) => println!(
+            "{:<12}{} completed, {} skipped ({:.0}% completed)",
+            "Work:",
+            stats.completed,
+            stats.skipped,
+            ratio * 100.0
+        ),
+        None => println!("{:<12}no work timers yet", "Work:"),
+    }
+
+    // the name of the kind goes on its first line only
+    for (kind, totals) in &stats.kinds {
+        let name = format!("{}:", status::capitalize(kind));
+
+        for (first, period, totals) in [
+            (true, "today", totals.today),
+            (false, "this week", totals.week),
+            (false, "this month", totals.month),
+        ] {
+            println!(
+                "{:<12}{} finished {}, {} spent, {} interruptions",
+                if first { name.as_str() } else { "" },
+                totals.pomodoros,
+                period,
+                format_hours(totals.focus_secs),
+                totals.interruptions
+            );
+        }
+    }
+}
+
+fn history_entries(session: Option<&str>, label: Option<&str>) -> std::io::Result<Vec<Entry>> {
+    let entries = History::default().entries()?;
+
+    Ok(entries
+        .into_iter()
+        .filter(|entry| session.is_none_or(|session| entry.session == session))
+        .filter(|entry| label.is_none_or(|label| entry.label.as_deref() == Some(label)))
+        .collect())
+}
+
+fn format_hours(secs: u64) -> String {
+    format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+}
+
+fn attach(args: Attach) {
+    let template = Template::parse(status::DEFAULT_TEMPLATE).expect("Invalid status template");
+
+    // ticks redraw the status every second, the subscription ends when the timer is stopped
+    let events = match connect(args.name).subscribe(true, false) {
+        Ok(events) => events,
+        Err(err) => exit_with_error(err),
+    };
+
+    for event in events {
+        let Ok(event) = event else {
+            break;
+        };
+        let status = &event.status;
+
+        // redraw the status on the same line
+        print!(
+            "\r\x1b[K{}{}{}",
+            template.render(status),
+            state_suffix(status.state),
+            label_suffix(status.label.as_deref())
+        );
+        std::io::stdout()
+            .flush()
+            .expect("Failed to write to stdout");
+
+        // ask once per finished timer, or right away for one that finished before attaching
+        let finished = match event.event {
+            EventKind::Timer(TimerEvent::Finish) => true,
+            EventKind::Status => status.state == TimerState::Stopped && status.remaining_secs == 0,
+            _ => false,
+        };
+
+        if finished && !status.auto {
+            println!();
+
+            match ask_next_timer() {
+                Ok(Some(commands)) => {
+                    // the subscription's connection only streams events
+                    let mut client = connect(Some(event.session.clone()));
+
+                    for command in commands {
+                        if let Err(err) = client.request(command) {
+                            eprintln!("{}", err);
+                        }
+                    }
+                }
+                Ok(None) => {}
+                Err(_) => return,
+            }
+        }
+    }
+
+    println!();
+    println!("Pomo has stopped.");
 }
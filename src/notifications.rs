@@ -0,0 +1,305 @@
+use crate::controller::Controller;
+use crate::daemon::DEFAULT_SESSION;
+use crate::status::{self, Status};
+use crate::timer::{Adjustment, Timer, TimerEvent};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use zbus::zvariant::Value;
+use zbus::{proxy, Connection};
+
+const APP_NAME: &str = "pomo";
+
+// how much time the "+5 min" button adds
+const EXTEND_BY: Duration = Duration::from_secs(5 * 60);
+
+#[proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    // start the next timer
+    Next,
+    // start the timer after the next one
+    SkipNext,
+    // add time to the current timer
+    Extend,
+}
+
+impl Action {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Action::Next => "next",
+            Action::SkipNext => "skip-next",
+            Action::Extend => "extend",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Action> {
+        match key {
+            "next" => Some(Action::Next),
+            "skip-next" => Some(Action::SkipNext),
+            "extend" => Some(Action::Extend),
+            _ => None,
+        }
+    }
+
+    fn run(&self, controller: &Arc<Mutex<Controller>>) {
+        let started = match self {
+            Action::Next => Controller::next(controller),
+            Action::SkipNext => {
+                Controller::next(controller).and_then(|_| Controller::next(controller))
+            }
+            Action::Extend => {
+                Controller::adjust(controller, Adjustment::Add, EXTEND_BY);
+                Ok(())
+            }
+        };
+
+        if let Err(reason) = started {
+            eprintln!("The next timer was not started: {}", reason);
+        }
+    }
+}
+
+pub struct Message {
+    pub summary: String,
+    pub body: String,
+    pub actions: Vec<(Action, String)>,
+    // urgent notifications stay until they are dismissed
+    pub urgent: bool,
+}
+
+// a notification whose buttons act on the timer it was shown for
+struct Pending {
+    controller: Weak<Mutex<Controller>>,
+    timer: Weak<Mutex<Timer>>,
+}
+
+// shows desktop notifications through the org.freedesktop.Notifications D-Bus interface
+#[derive(Clone)]
+pub struct Notifier {
+    proxy: NotificationsProxy<'static>,
+    pending: Arc<Mutex<HashMap<u32, Pending>>>,
+}
+
+impl Notifier {
+    pub async fn connect() -> zbus::Result<Notifier> {
+        Notifier::with_connection(Connection::session().await?).await
+    }
+
+    // use any bus, e.g. a private one for testing
+    pub async fn with_connection(connection: Connection) -> zbus::Result<Notifier> {
+        let proxy = NotificationsProxy::new(&connection).await?;
+
+        let notifier = Notifier {
+            proxy,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        notifier.listen().await?;
+
+        Ok(notifier)
+    }
+
+    // show notifications for the timers of a session, the name is shown for other than the
+    // default session
+    pub fn watch(&self, session: &str, controller: &Arc<Mutex<Controller>>) {
+        for event in [TimerEvent::Start, TimerEvent::Finish] {
+            let notifier = self.clone();
+            let session = session.to_string();
+            let controller_ref = Arc::downgrade(controller);
+
+            Controller::on(
+                controller,
+                event,
+                Arc::new(move |_: &Timer| {
+                    let notifier = notifier.clone();
+                    let session = session.clone();
+                    let controller = controller_ref.clone();
+
+                    // the controller is locked while its event handlers run, so look at it and
+                    // talk to the notification server later
+                    tokio::spawn(async move {
+                        if let Some(controller) = controller.upgrade() {
+                            notifier.timer_event(&session, event, &controller).await;
+                        }
+                    });
+                }),
+            );
+        }
+    }
+
+    async fn timer_event(
+        &self,
+        session: &str,
+        event: TimerEvent,
+        controller: &Arc<Mutex<Controller>>,
+    ) {
+        let status = Controller::status(controller);
+
+        // in auto mode the next timer starts right away and gets its own notification
+        if event == TimerEvent::Finish && status.auto {
+            return;
+        }
+
+        let timer = Controller::get_current_timer(controller);
+        let message = message(session, event, &status);
+
+        match self.notify(&message).await {
+            Ok(id) if !message.actions.is_empty() => {
+                let mut pending = self.pending.lock().expect("Failed to lock notifications");
+
+                pending.insert(
+                    id,
+                    Pending {
+                        controller: Arc::downgrade(controller),
+                        timer: Arc::downgrade(&timer),
+                    },
+                );
+            }
+            Ok(_) => {}
+            Err(err) => eprintln!("Failed to show notification: {}", err),
+        }
+    }
+
+    pub async fn notify(&self, message: &Message) -> zbus::Result<u32> {
+        // actions are sent as a flat list of key and label pairs
+        let actions: Vec<&str> = message
+            .actions
+            .iter()
+            .flat_map(|(action, label)| [action.key(), label.as_str()])
+            .collect();
+
+        let urgency: u8 = if message.urgent { 2 } else { 1 };
+
+        let mut hints = HashMap::new();
+        hints.insert("urgency", Value::from(urgency));
+
+        self.proxy
+            .notify(
+                APP_NAME,
+                0,
+                "",
+                &message.summary,
+                &message.body,
+                &actions,
+                hints,
+                -1,
+            )
+            .await
+    }
+
+    async fn listen(&self) -> zbus::Result<()> {
+        let mut invoked = self.proxy.receive_action_invoked().await?;
+        let mut closed = self.proxy.receive_notification_closed().await?;
+        let pending = Arc::clone(&self.pending);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(signal) = invoked.next() => {
+                        if let Ok(args) = signal.args() {
+                            let notification = take(&pending, args.id);
+                            let key = args.action_key.to_string();
+
+                            // starting the next timer waits for the before hooks
+                            tokio::task::spawn_blocking(move || run_action(notification, &key));
+                        }
+                    }
+                    Some(signal) = closed.next() => {
+                        if let Ok(args) = signal.args() {
+                            take(&pending, args.id);
+                        }
+                    }
+                    else => break,
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+fn take(pending: &Mutex<HashMap<u32, Pending>>, id: u32) -> Option<Pending> {
+    pending
+        .lock()
+        .expect("Failed to lock notifications")
+        .remove(&id)
+}
+
+fn run_action(notification: Option<Pending>, key: &str) {
+    let Some(Pending { controller, timer }) = notification else {
+        return;
+    };
+
+    let (Some(controller), Some(timer), Some(action)) =
+        (controller.upgrade(), timer.upgrade(), Action::from_key(key))
+    else {
+        return;
+    };
+
+    // ignore buttons of a notification about a timer that has been replaced in the meantime
+    if !Arc::ptr_eq(&Controller::get_current_timer(&controller), &timer) {
+        return;
+    }
+
+    action.run(&controller);
+}
+
+fn message(session: &str, event: TimerEvent, status: &Status) -> Message {
+    let name = if session == DEFAULT_SESSION {
+        String::new()
+    } else {
+        format!(" ({})", session)
+    };
+
+    let extend = (Action::Extend, "+5 min".to_string());
+
+    match event {
+        TimerEvent::Finish => Message {
+            summary: format!("{} timer finished{}", status.title(), name),
+            body: format!(
+                "Up next: {} for {}.",
+                status.next_timer_type,
+                status::format_clock(status.next_duration_secs)
+            ),
+            actions: vec![
+                (Action::Next, format!("Start {}", status.next_timer_type)),
+                (Action::SkipNext, format!("Skip {}", status.next_timer_type)),
+                extend,
+            ],
+            urgent: true,
+        },
+        _ => Message {
+            summary: format!("{} timer started{}", status.title(), name),
+            body: format!("{} left.", status::format_clock(status.remaining_secs)),
+            actions: vec![(Action::Next, "Skip".to_string()), extend],
+            urgent: false,
+        },
+    }
+}
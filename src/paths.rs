@@ -0,0 +1,139 @@
+use crate::peer;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+
+// the config directory follows XDG on every platform, so hooks and config live in ~/.config/pomo
+pub fn config_dir() -> PathBuf {
+    let mut path = match std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(path) if path.is_absolute() => path,
+        _ => {
+            let mut path = dirs::home_dir().expect("Failed to get home directory");
+            path.push(".config");
+            path
+        }
+    };
+    path.push("pomo");
+    path
+}
+
+pub fn config_file() -> PathBuf {
+    match std::env::var_os("POMO_CONFIG") {
+        Some(path) => PathBuf::from(path),
+        None => config_dir().join("config.toml"),
+    }
+}
+
+pub fn hooks_dir() -> PathBuf {
+    config_dir().join("hooks")
+}
+
+pub fn data_dir() -> PathBuf {
+    let mut path = dirs::data_dir().expect("Failed to get data directory");
+    path.push("pomo");
+    path
+}
+
+pub fn history_file() -> PathBuf {
+    data_dir().join("history.jsonl")
+}
+
+pub fn tasks_file() -> PathBuf {
+    data_dir().join("tasks.json")
+}
+
+pub fn state_dir() -> PathBuf {
+    let mut path = dirs::state_dir()
+        .or_else(dirs::data_dir)
+        .expect("Failed to get state directory");
+    path.push("pomo");
+    path
+}
+
+pub fn state_file(session: &str) -> PathBuf {
+    state_dir()
+        .join("sessions")
+        .join(format!("{}.json", session))
+}
+
+pub fn pid_file() -> PathBuf {
+    state_dir().join("pomo.pid")
+}
+
+pub fn log_file() -> PathBuf {
+    state_dir().join("pomo.log")
+}
+
+pub fn hooks_log_file() -> PathBuf {
+    state_dir().join("hooks.log")
+}
+
+// sockets live in a per-user directory, $XDG_RUNTIME_DIR/pomo or /tmp/pomo-<uid> without one
+pub fn runtime_dir() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(path) => path.join("pomo"),
+        None => std::env::temp_dir().join(format!("pomo-{}", peer::current_uid())),
+    }
+}
+
+pub fn socket_file() -> PathBuf {
+    match std::env::var_os("POMO_SOCKET") {
+        Some(path) => PathBuf::from(path),
+        None => runtime_dir().join("pomo.sock"),
+    }
+}
+
+// creates a directory only the current user can access, refusing a symlink, or one that belongs to
+// someone else
+pub fn ensure_private_dir(path: &Path) -> io::Result<()> {
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(path)?;
+
+    // don't follow a symlink someone put in place of the directory
+    let metadata = fs::symlink_metadata(path)?;
+
+    if !metadata.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a directory", path.display()),
+        ));
+    }
+
+    if metadata.uid() != peer::current_uid() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} belongs to another user", path.display()),
+        ));
+    }
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+}
+
+// binds a socket only the current user can connect to, it never exists with wider permissions
+pub fn bind_private(path: &Path) -> io::Result<UnixListener> {
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(umask) };
+
+    listener
+}
+
+// writes to a temporary file first so a crash halfway through never leaves a corrupt file behind
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+
+    fs::rename(&temp_path, path)
+}
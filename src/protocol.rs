@@ -0,0 +1,294 @@
+use crate::controller::{Config, Snapshot};
+use crate::duration;
+pub use crate::status::Status;
+use crate::timer::{Adjustment, InterruptionKind, TimerEvent};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+// messages are framed as newline-delimited JSON, one request or response object per line
+pub const PROTOCOL_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Request {
+    pub version: u32,
+    pub id: u64,
+    // name of the session the command is for, see `Daemon::session` for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+// the names of the commands, a request for any other command is unknown rather than invalid
+pub const COMMANDS: &[&str] = &[
+    "start",
+    "list",
+    "label",
+    "pause",
+    "resume",
+    "stop",
+    "next",
+    "adjust",
+    "interrupt",
+    "status",
+    "subscribe",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Start {
+        config: Config,
+        #[serde(default)]
+        snapshot: Option<Box<Snapshot>>,
+        #[serde(default)]
+        replace: bool,
+        // replaces the label of a restored timer
+        #[serde(default)]
+        label: Option<String>,
+        // the id of the task from the task list the label stands for
+        #[serde(default)]
+        task: Option<u64>,
+    },
+    List,
+    // set or, without a label, remove the label of the current timer
+    Label {
+        #[serde(default)]
+        label: Option<String>,
+        #[serde(default)]
+        task: Option<u64>,
+    },
+    Pause,
+    Resume,
+    Stop,
+    Next,
+    Adjust {
+        adjustment: Adjustment,
+        #[serde(with = "duration::secs")]
+        duration: Duration,
+    },
+    // count an interruption of the current work timer, and optionally pause it
+    Interrupt {
+        #[serde(default)]
+        kind: InterruptionKind,
+        #[serde(default)]
+        note: Option<String>,
+        #[serde(default)]
+        pause: bool,
+    },
+    Status,
+    // keep the connection open and stream events of the session, or of all sessions, see `Event`
+    Subscribe {
+        #[serde(default)]
+        ticks: bool,
+        #[serde(default)]
+        all: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Response {
+    pub version: u32,
+    // the id is missing if the request could not be parsed far enough to read it
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Result(Reply),
+    Error(Error),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply {
+    Ok,
+    Status(Box<Status>),
+    Sessions { sessions: Vec<SessionStatus> },
+}
+
+// sent to subscribed clients, without an id since it doesn't answer a request
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Event {
+    pub version: u32,
+    pub session: String,
+    pub event: EventKind,
+    pub status: Status,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    // the state of a session when the subscription starts
+    Status,
+    // sent every second when subscribed with ticks
+    Tick,
+    #[serde(untagged)]
+    Timer(TimerEvent),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionStatus {
+    pub name: String,
+    pub status: Status,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidRequest,
+    VersionMismatch,
+    UnknownCommand,
+    PermissionDenied,
+    SessionNotFound,
+    SessionExists,
+    InvalidSessionName,
+    InvalidState,
+    // a before-start or before-break hook refused to start the timer
+    Cancelled,
+}
+
+impl Request {
+    pub fn new(id: u64, session: Option<String>, command: Command) -> Request {
+        Request {
+            version: PROTOCOL_VERSION,
+            id,
+            session,
+            command,
+        }
+    }
+
+    // the version is checked before the command, so clients speaking another version of the
+    // protocol get a version error instead of a parse error
+    pub fn parse(line: &str) -> Result<Request, Response> {
+        let value: serde_json::Value = serde_json::from_str(line).map_err(|_| {
+            Response::error(
+                None,
+                ErrorCode::InvalidRequest,
+                "Expected a JSON request. The client is probably older than the daemon, try updating pomo.",
+            )
+        })?;
+
+        let id = value.get("id").and_then(|id| id.as_u64());
+
+        match value.get("version").and_then(|version| version.as_u64()) {
+            Some(version) if version == u64::from(PROTOCOL_VERSION) => {}
+            Some(version) => {
+                return Err(Response::error(
+                    id,
+                    ErrorCode::VersionMismatch,
+                    &format!(
+                        "Protocol version {} is not supported, the daemon speaks version {}.",
+                        version, PROTOCOL_VERSION
+                    ),
+                ));
+            }
+            None => {
+                return Err(Response::error(
+                    id,
+                    ErrorCode::InvalidRequest,
+                    "Request is missing the protocol version.",
+                ));
+            }
+        }
+
+        // a known command with bad arguments, e.g. a start without a config or an unknown
+        // adjustment, is an invalid request instead
+        if let Some(command) = value.get("command").and_then(|command| command.as_str()) {
+            if !COMMANDS.contains(&command) {
+                return Err(Response::error(
+                    id,
+                    ErrorCode::UnknownCommand,
+                    &format!("Unknown command '{}'.", command),
+                ));
+            }
+        }
+
+        serde_json::from_value(value)
+            .map_err(|err| Response::error(id, ErrorCode::InvalidRequest, &err.to_string()))
+    }
+}
+
+impl Response {
+    pub fn result(id: u64, reply: Reply) -> Response {
+        Response {
+            version: PROTOCOL_VERSION,
+            id: Some(id),
+            outcome: Outcome::Result(reply),
+        }
+    }
+
+    pub fn error(id: Option<u64>, code: ErrorCode, message: &str) -> Response {
+        Response {
+            version: PROTOCOL_VERSION,
+            id,
+            outcome: Outcome::Error(Error::new(code, message)),
+        }
+    }
+}
+
+impl Event {
+    pub fn new(session: &str, event: EventKind, status: Status) -> Event {
+        Event {
+            version: PROTOCOL_VERSION,
+            session: session.to_string(),
+            event,
+            status,
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventKind::Status => write!(f, "status"),
+            EventKind::Tick => write!(f, "tick"),
+            EventKind::Timer(event) => write!(f, "{}", event),
+        }
+    }
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: &str) -> Error {
+        Error {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+
+    writer.write_all(&line)?;
+    writer.flush()
+}
+
+// returns None when the other side has closed the connection
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(line.trim_end().to_string()))
+}
@@ -0,0 +1,94 @@
+use crate::timer::TimerType;
+use serde::{Deserialize, Serialize};
+
+// how a work timer ended, a timer that is stopped or skipped before its time is up was skipped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ending {
+    Finished,
+    Skipped,
+}
+
+// the work timers so far, saved with the state of a timer
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct WorkCounts {
+    pub completed: u64,
+    pub skipped: u64,
+    // the work timers counted towards the long break since the last one
+    pub since_long_break: u64,
+}
+
+// Decides whether the break after a work timer is a long one. Every `interval` work timers are
+// followed by a long break, an interval of 0 means there are no long breaks. Skipped work timers
+// only count towards it with `count_skipped`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scheduler {
+    interval: u64,
+    count_skipped: bool,
+    counts: WorkCounts,
+}
+
+impl Scheduler {
+    pub fn new(interval: u64, count_skipped: bool) -> Scheduler {
+        Scheduler::restore(interval, count_skipped, WorkCounts::default())
+    }
+
+    pub fn restore(interval: u64, count_skipped: bool, counts: WorkCounts) -> Scheduler {
+        Scheduler {
+            interval,
+            count_skipped,
+            counts,
+        }
+    }
+
+    pub fn counts(&self) -> WorkCounts {
+        self.counts
+    }
+
+    // the break that follows the current work timer if it ends this way
+    pub fn break_after(&self, ending: Ending) -> TimerType {
+        let counted = self.counts.since_long_break + u64::from(self.counted(ending));
+
+        if self.interval != 0 && counted >= self.interval {
+            TimerType::LongBreak
+        } else {
+            TimerType::Break
+        }
+    }
+
+    pub fn work_ended(&mut self, ending: Ending) {
+        match ending {
+            Ending::Finished => self.counts.completed += 1,
+            Ending::Skipped => self.counts.skipped += 1,
+        }
+
+        if self.counted(ending) {
+            self.counts.since_long_break += 1;
+        }
+    }
+
+    // a long break starts the next cycle
+    pub fn long_break_started(&mut self) {
+        self.counts.since_long_break = 0;
+    }
+
+    // the work timer of the cycle, counting from 1, that is running or was just done. None
+    // without long breaks.
+    pub fn position(&self, timer_type: TimerType) -> Option<u64> {
+        if self.interval == 0 {
+            return None;
+        }
+
+        let position = match timer_type {
+            TimerType::Work => self.counts.since_long_break + 1,
+            TimerType::Break => self.counts.since_long_break,
+            TimerType::LongBreak => self.interval,
+        };
+
+        Some(position.clamp(1, self.interval))
+    }
+
+    // whether the work timer counts towards the long break
+    fn counted(&self, ending: Ending) -> bool {
+        ending == Ending::Finished || self.count_skipped
+    }
+}
@@ -0,0 +1,170 @@
+use crate::duration;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+// shown in error messages
+pub const EXAMPLE: &str = "work 50m, break 10m, work 50m, long 30m";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceError(String);
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid sequence step '{}', use for example \"{}\" and end with \", stop\" to not \
+             repeat it.",
+            self.0, EXAMPLE
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    // "work", "break", "long" or a kind from the config file, such as "meeting"
+    pub kind: String,
+    pub duration: Duration,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, format_duration(self.duration))
+    }
+}
+
+// The timers to go through in place of the usual work and break alternation, such as
+// "work 50m, break 10m, work 50m, long 30m". It starts over after the last step, unless it ends
+// with "stop".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    steps: Vec<Step>,
+    repeat: bool,
+}
+
+impl Sequence {
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn repeats(&self) -> bool {
+        self.repeat
+    }
+
+    // the step at `position`, which is always one of the steps
+    pub fn step(&self, position: usize) -> &Step {
+        &self.steps[position % self.steps.len()]
+    }
+
+    // None after the last step of a sequence that doesn't repeat
+    pub fn next_position(&self, position: usize) -> Option<usize> {
+        match position + 1 {
+            next if next < self.steps.len() => Some(next),
+            _ if self.repeat => Some(0),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Sequence {
+    type Err = SequenceError;
+
+    fn from_str(input: &str) -> Result<Sequence, SequenceError> {
+        let mut parts = input.split(',').map(str::trim).collect::<Vec<_>>();
+
+        let repeat = match parts.last() {
+            Some(&"stop") => {
+                parts.pop();
+                false
+            }
+            Some(&"repeat") => {
+                parts.pop();
+                true
+            }
+            _ => true,
+        };
+
+        if parts.iter().all(|part| part.is_empty()) {
+            return Err(SequenceError(input.to_string()));
+        }
+
+        let steps = parts
+            .into_iter()
+            .map(parse_step)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Sequence { steps, repeat })
+    }
+}
+
+// "work 50m", "break 10", "long 30m" or "meeting 30m", whether a kind such as "meeting" exists is
+// up to the config
+fn parse_step(input: &str) -> Result<Step, SequenceError> {
+    let error = || SequenceError(input.to_string());
+
+    let (kind, length) = input.split_once(char::is_whitespace).ok_or_else(error)?;
+
+    let valid_kind = kind
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid_kind || kind == "stop" || kind == "repeat" {
+        return Err(error());
+    }
+
+    let duration = duration::parse(length).map_err(|_| error())?;
+
+    Ok(Step {
+        kind: kind.to_string(),
+        duration,
+    })
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps = self
+            .steps
+            .iter()
+            .map(Step::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self.repeat {
+            true => write!(f, "{}", steps),
+            false => write!(f, "{}, stop", steps),
+        }
+    }
+}
+
+// written as text in the config file, the protocol and state files
+impl Serialize for Sequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Sequence, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+// "1h30m", "50m" or "90s", the shortest way to write it
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    let mut output = String::new();
+
+    for (value, unit) in [(hours, 'h'), (minutes, 'm'), (secs, 's')] {
+        if value != 0 {
+            output.push_str(&format!("{}{}", value, unit));
+        }
+    }
+
+    output
+}
@@ -0,0 +1,78 @@
+use crate::controller::Snapshot;
+use crate::paths;
+use crate::timer::TimerState;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedState {
+    pub saved_at: DateTime<Utc>,
+    pub controller: Snapshot,
+}
+
+// what to do with the time that passed while pomo wasn't running
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DowntimePolicy {
+    // the downtime counts as elapsed time of a running timer
+    Count,
+    // the timer continues where it was when it was last saved
+    Discard,
+}
+
+impl FromStr for DowntimePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<DowntimePolicy, String> {
+        match value {
+            "count" => Ok(DowntimePolicy::Count),
+            "discard" => Ok(DowntimePolicy::Discard),
+            _ => Err(format!(
+                "Invalid downtime policy '{}', expected 'count' or 'discard'.",
+                value
+            )),
+        }
+    }
+}
+
+impl SavedState {
+    pub fn new(controller: Snapshot) -> SavedState {
+        SavedState {
+            saved_at: Utc::now(),
+            controller,
+        }
+    }
+
+    // returns the controller snapshot as it should be restored at `now`
+    pub fn restore(self, policy: DowntimePolicy, now: DateTime<Utc>) -> Snapshot {
+        let mut snapshot = self.controller;
+
+        if policy == DowntimePolicy::Count && snapshot.timer.state == TimerState::Running {
+            let downtime = (now - self.saved_at).to_std().unwrap_or_default();
+            snapshot.timer.remaining = snapshot.timer.remaining.saturating_sub(downtime);
+        }
+
+        snapshot
+    }
+}
+
+pub fn load(path: &Path) -> io::Result<Option<SavedState>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+pub fn save(path: &Path, state: &SavedState) -> io::Result<()> {
+    paths::write_atomic(path, &serde_json::to_vec(state)?)
+}
+
+pub fn remove(path: &Path) {
+    fs::remove_file(path).unwrap_or(());
+}
@@ -0,0 +1,319 @@
+use crate::timer::{Interruptions, TimerState, TimerType};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const DEFAULT_TEMPLATE: &str = "{prefix} {remaining}";
+
+const PLACEHOLDERS: &[&str] = &[
+    "type",
+    "kind",
+    "prefix",
+    "state",
+    "remaining",
+    "remaining_secs",
+    "total",
+    "total_secs",
+    "elapsed",
+    "elapsed_secs",
+    "cycle",
+    "completed",
+    "skipped",
+    "next",
+    "next_duration",
+    "next_duration_secs",
+    "auto",
+    "label",
+    "interruptions",
+    "internal",
+    "external",
+    "step",
+    "steps",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Status {
+    pub timer_type: TimerType,
+    pub state: TimerState,
+    pub remaining_secs: u64,
+    pub total_secs: u64,
+    pub elapsed_secs: u64,
+    pub cycle: u64,
+    #[serde(default)]
+    pub long_break_interval: u64,
+    // work timers completed and skipped so far
+    #[serde(default)]
+    pub completed: u64,
+    #[serde(default)]
+    pub skipped: u64,
+    // the work timer of the cycle towards the long break, counting from 1, None without long
+    // breaks
+    #[serde(default)]
+    pub cycle_position: Option<u64>,
+    pub next_timer_type: TimerType,
+    pub next_duration_secs: u64,
+    pub auto: bool,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub interruptions: Interruptions,
+    // where the timer is in the sequence, if one is used
+    #[serde(default)]
+    pub sequence: Option<SequenceStatus>,
+    // a kind from the config file, such as "meeting", and its prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SequenceStatus {
+    // counting from 1
+    pub step: usize,
+    pub steps: usize,
+    pub repeat: bool,
+}
+
+#[derive(Debug)]
+pub struct TemplateError(String);
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+// a status template such as "{prefix} {remaining}", use {{ and }} for literal braces
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Template, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(TemplateError(format!(
+                                    "Unclosed placeholder '{{{}' in template.",
+                                    name
+                                )));
+                            }
+                        }
+                    }
+
+                    if !PLACEHOLDERS.contains(&name.as_str()) {
+                        return Err(TemplateError(format!(
+                            "Unknown placeholder '{{{}}}'. Available placeholders: {}.",
+                            name,
+                            PLACEHOLDERS.join(", ")
+                        )));
+                    }
+
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Placeholder(name));
+                }
+                '}' => {
+                    return Err(TemplateError(
+                        "Unmatched '}' in template, use '}}' for a literal brace.".to_string(),
+                    ));
+                }
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Template { segments })
+    }
+
+    pub fn render(&self, status: &Status) -> String {
+        let mut output = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Placeholder(name) => output.push_str(&status.placeholder(name)),
+            }
+        }
+
+        output
+    }
+}
+
+impl Status {
+    // e.g. "Work", "Long break" or "Meeting"
+    pub fn title(&self) -> String {
+        match (&self.kind, self.timer_type) {
+            (Some(kind), _) => capitalize(kind),
+            (None, TimerType::LongBreak) => "Long break".to_string(),
+            (None, timer_type) => capitalize(timer_type),
+        }
+    }
+
+    fn placeholder(&self, name: &str) -> String {
+        match name {
+            "type" => self.timer_type.to_string(),
+            "kind" => self
+                .kind
+                .clone()
+                .unwrap_or_else(|| self.timer_type.to_string()),
+            "prefix" => self
+                .prefix
+                .clone()
+                .unwrap_or_else(|| prefix(self.timer_type).to_string()),
+            "state" => self.state.to_string(),
+            "remaining" => format_clock(self.remaining_secs),
+            "remaining_secs" => self.remaining_secs.to_string(),
+            "total" => format_clock(self.total_secs),
+            "total_secs" => self.total_secs.to_string(),
+            "elapsed" => format_clock(self.elapsed_secs),
+            "elapsed_secs" => self.elapsed_secs.to_string(),
+            "cycle" => self.cycle.to_string(),
+            "completed" => self.completed.to_string(),
+            "skipped" => self.skipped.to_string(),
+            "next" => self.next_timer_type.to_string(),
+            "next_duration" => format_clock(self.next_duration_secs),
+            "next_duration_secs" => self.next_duration_secs.to_string(),
+            "auto" => self.auto.to_string(),
+            "label" => self.label.clone().unwrap_or_default(),
+            "interruptions" => self.interruptions.total().to_string(),
+            "internal" => self.interruptions.internal.to_string(),
+            "external" => self.interruptions.external.to_string(),
+            "step" => self
+                .sequence
+                .map(|sequence| sequence.step.to_string())
+                .unwrap_or_default(),
+            "steps" => self
+                .sequence
+                .map(|sequence| sequence.steps.to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+pub fn prefix(timer_type: TimerType) -> &'static str {
+    match timer_type {
+        TimerType::Work => "W",
+        TimerType::Break => "B",
+        TimerType::LongBreak => "L",
+    }
+}
+
+// formats seconds as mm:ss
+pub fn format_clock(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+// e.g. "work" becomes "Work"
+pub fn capitalize(value: impl ToString) -> String {
+    let value = value.to_string();
+    let mut chars = value.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> Status {
+        Status {
+            timer_type: TimerType::Work,
+            state: TimerState::Running,
+            remaining_secs: 1498,
+            total_secs: 1500,
+            elapsed_secs: 2,
+            cycle: 3,
+            long_break_interval: 4,
+            completed: 2,
+            skipped: 1,
+            cycle_position: Some(3),
+            next_timer_type: TimerType::Break,
+            next_duration_secs: 300,
+            auto: false,
+            label: Some("review".to_string()),
+            interruptions: Interruptions::default(),
+            sequence: None,
+            kind: None,
+            prefix: None,
+        }
+    }
+
+    fn render(template: &str) -> String {
+        Template::parse(template)
+            .expect("Failed to parse template")
+            .render(&status())
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(render(DEFAULT_TEMPLATE), "W 24:58");
+        assert_eq!(
+            render("{type} {state} {remaining_secs}/{total} {label}"),
+            "work running 1498/25:00 review"
+        );
+        assert_eq!(
+            render("{completed}+{skipped} next: {next} {next_duration}"),
+            "2+1 next: break 05:00"
+        );
+
+        // placeholders without a value are empty
+        assert_eq!(render("[{step}/{steps}]"), "[/]");
+        assert_eq!(render("no placeholders"), "no placeholders");
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        let err = Template::parse("{prefix} {left}").err().unwrap();
+        assert!(err.to_string().starts_with("Unknown placeholder '{left}'."));
+
+        let err = Template::parse("{remaining").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unclosed placeholder '{remaining' in template."
+        );
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(render("{{{prefix}}} {{remaining}}"), "{W} {remaining}");
+
+        let err = Template::parse("{prefix}}").err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unmatched '}' in template, use '}}' for a literal brace."
+        );
+    }
+}
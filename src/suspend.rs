@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tokio::time::{Duration, Instant};
+
+// shorter differences between the clocks are taken to be noise
+const THRESHOLD: Duration = Duration::from_secs(2);
+
+// a clock that keeps going while the system is suspended, unlike the monotonic clock timers run
+// on. On macOS the monotonic clock of clock_gettime already does.
+#[cfg(target_os = "linux")]
+const BOOT_CLOCK: libc::clockid_t = libc::CLOCK_BOOTTIME;
+#[cfg(not(target_os = "linux"))]
+const BOOT_CLOCK: libc::clockid_t = libc::CLOCK_MONOTONIC;
+
+// what happens to a running timer when the system was suspended
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SuspendPolicy {
+    // the suspended time counts as elapsed, as if the timer kept running
+    #[default]
+    Count,
+    // the timer is paused where it was when the system was suspended
+    Pause,
+    // the timer finishes as soon as the system wakes up
+    Finish,
+}
+
+impl FromStr for SuspendPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<SuspendPolicy, String> {
+        match value {
+            "count" => Ok(SuspendPolicy::Count),
+            "pause" => Ok(SuspendPolicy::Pause),
+            "finish" => Ok(SuspendPolicy::Finish),
+            _ => Err(format!(
+                "Invalid suspend policy '{}', expected 'count', 'pause' or 'finish'.",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SuspendPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            SuspendPolicy::Count => "count",
+            SuspendPolicy::Pause => "pause",
+            SuspendPolicy::Finish => "finish",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+// Notices that the system was suspended by comparing the monotonic clock, which stops during
+// suspend, with the boot clock, which doesn't. Both only move forward, so any difference between
+// how far they moved since the last check is time the system spent suspended.
+pub struct SuspendDetector {
+    monotonic: Instant,
+    boot: Duration,
+}
+
+impl SuspendDetector {
+    pub fn new() -> SuspendDetector {
+        SuspendDetector::starting_at(Instant::now(), boot_time())
+    }
+
+    pub fn starting_at(monotonic: Instant, boot: Duration) -> SuspendDetector {
+        SuspendDetector { monotonic, boot }
+    }
+
+    // how long the system was suspended since the last check, if it was
+    pub fn check(&mut self) -> Option<Duration> {
+        self.check_at(Instant::now(), boot_time())
+    }
+
+    pub fn check_at(&mut self, monotonic: Instant, boot: Duration) -> Option<Duration> {
+        let gap = boot
+            .saturating_sub(self.boot)
+            .saturating_sub(monotonic.saturating_duration_since(self.monotonic));
+
+        self.monotonic = monotonic;
+        self.boot = boot;
+
+        (gap >= THRESHOLD).then_some(gap)
+    }
+}
+
+impl Default for SuspendDetector {
+    fn default() -> SuspendDetector {
+        SuspendDetector::new()
+    }
+}
+
+fn boot_time() -> Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+
+    // can only fail for an unknown clock
+    unsafe {
+        libc::clock_gettime(BOOT_CLOCK, &mut time);
+    }
+
+    Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
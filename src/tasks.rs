@@ -0,0 +1,132 @@
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    pub id: u64,
+    pub title: String,
+    // how many pomodoros the task is expected to take
+    #[serde(default)]
+    pub estimate: Option<u64>,
+    // how many work timers were finished while the task was active
+    #[serde(default)]
+    pub pomodoros: u64,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.estimate {
+            Some(estimate) => write!(f, "{} ({}/{})", self.title, self.pomodoros, estimate),
+            None => write!(f, "{} ({})", self.title, self.pomodoros),
+        }
+    }
+}
+
+// the task list, shared by all timers
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct TaskList {
+    #[serde(default)]
+    tasks: Vec<Task>,
+    // the task that finished work timers are credited to
+    #[serde(default)]
+    active: Option<u64>,
+}
+
+impl TaskList {
+    // a missing file is an empty list
+    pub fn load(path: &Path) -> io::Result<TaskList> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(TaskList::default()),
+            Err(err) => return Err(err),
+        };
+
+        serde_json::from_slice(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        paths::write_atomic(path, &serde_json::to_vec_pretty(self)?)
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    // tasks that are not done yet, in the order they were added
+    pub fn open(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|task| !task.done)
+    }
+
+    pub fn active(&self) -> Option<&Task> {
+        let id = self.active?;
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    // the first task that is added becomes the active one
+    pub fn add(&mut self, title: &str, estimate: Option<u64>) -> &Task {
+        let id = self.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+
+        self.tasks.push(Task {
+            id,
+            title: title.to_string(),
+            estimate,
+            pomodoros: 0,
+            done: false,
+        });
+
+        if self.active().is_none() {
+            self.active = Some(id);
+        }
+
+        self.tasks.last().expect("Failed to add task")
+    }
+
+    // returns false if there is no open task with this id
+    pub fn activate(&mut self, id: u64) -> bool {
+        if !self.open().any(|task| task.id == id) {
+            return false;
+        }
+
+        self.active = Some(id);
+        true
+    }
+
+    // marks the active task as done and moves on to the next open one
+    pub fn complete_active(&mut self) -> Option<&Task> {
+        let position = self
+            .active
+            .and_then(|id| self.tasks.iter().position(|task| task.id == id));
+
+        if let Some(position) = position {
+            self.tasks[position].done = true;
+        }
+
+        // prefer the tasks after the one that was just done
+        let start = position.map_or(0, |position| position + 1);
+
+        let next = self.tasks[start..]
+            .iter()
+            .chain(&self.tasks[..start])
+            .find(|task| !task.done)
+            .map(|task| task.id);
+
+        self.active = next;
+        self.active()
+    }
+
+    // credit a finished work timer to the task it was spent on
+    pub fn credit(&mut self, id: u64) -> Option<&Task> {
+        let task = self.tasks.iter_mut().find(|task| task.id == id)?;
+
+        task.pomodoros += 1;
+
+        Some(task)
+    }
+}
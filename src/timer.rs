@@ -1,3 +1,5 @@
+use crate::suspend::SuspendPolicy;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
@@ -5,10 +7,20 @@ use std::sync::Mutex;
 use tokio::task;
 use tokio::time::{Duration, Instant};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TimerType {
     Work,
     Break,
+    LongBreak,
+}
+
+impl TimerType {
+    pub const ALL: [TimerType; 3] = [TimerType::Work, TimerType::Break, TimerType::LongBreak];
+
+    pub fn is_break(self) -> bool {
+        matches!(self, TimerType::Break | TimerType::LongBreak)
+    }
 }
 
 impl fmt::Display for TimerType {
@@ -16,84 +28,365 @@ impl fmt::Display for TimerType {
         let string = match self {
             TimerType::Work => "work",
             TimerType::Break => "break",
+            TimerType::LongBreak => "long_break",
         };
 
         write!(f, "{}", string)
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TimerEvent {
     Finish,
     Start,
     Pause,
+    Resume,
     Stop,
+    Skip,
+    Adjust,
+    Interrupt,
+    // the system was suspended while the timer was active, reported once it wakes up, right
+    // before the wake event
+    Suspend,
+    Wake,
+}
+
+impl TimerEvent {
+    pub const ALL: [TimerEvent; 10] = [
+        TimerEvent::Start,
+        TimerEvent::Pause,
+        TimerEvent::Resume,
+        TimerEvent::Adjust,
+        TimerEvent::Interrupt,
+        TimerEvent::Skip,
+        TimerEvent::Stop,
+        TimerEvent::Finish,
+        TimerEvent::Suspend,
+        TimerEvent::Wake,
+    ];
+}
+
+pub type EventHandler = Arc<dyn Fn(&Timer) + Send + Sync>;
+
+impl fmt::Display for TimerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            TimerEvent::Finish => "finish",
+            TimerEvent::Start => "start",
+            TimerEvent::Pause => "pause",
+            TimerEvent::Resume => "resume",
+            TimerEvent::Stop => "stop",
+            TimerEvent::Skip => "skip",
+            TimerEvent::Adjust => "adjust",
+            TimerEvent::Interrupt => "interrupt",
+            TimerEvent::Suspend => "suspend",
+            TimerEvent::Wake => "wake",
+        };
+
+        write!(f, "{}", string)
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TimerState {
     Running,
     Paused,
     Stopped,
 }
 
+impl fmt::Display for TimerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            TimerState::Running => "running",
+            TimerState::Paused => "paused",
+            TimerState::Stopped => "stopped",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+// how an adjustment changes the time left on a timer
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Adjustment {
+    Add,
+    Subtract,
+    Set,
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            Adjustment::Add => "add",
+            Adjustment::Subtract => "subtract",
+            Adjustment::Set => "set",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+// internal interruptions come from yourself, external ones from others
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterruptionKind {
+    #[default]
+    Internal,
+    External,
+}
+
+impl fmt::Display for InterruptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let string = match self {
+            InterruptionKind::Internal => "internal",
+            InterruptionKind::External => "external",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Interruptions {
+    pub internal: u64,
+    pub external: u64,
+}
+
+impl Interruptions {
+    pub fn total(&self) -> u64 {
+        self.internal + self.external
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimerSnapshot {
+    pub timer_type: TimerType,
+    pub state: TimerState,
+    pub duration: Duration,
+    pub remaining: Duration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<u64>,
+    #[serde(default)]
+    pub interruptions: Vec<Interruption>,
+}
+
 pub struct Timer {
+    duration: Duration,
     remaining: Duration,
     handle: Option<tokio::task::JoinHandle<()>>,
+    // counts the sleep tasks, a task that already woke up when it was replaced mustn't finish the
+    // timer
+    generation: u64,
     state: TimerState,
     last_started_at: Option<Instant>,
     timer_type: TimerType,
-    event_handlers: HashMap<TimerEvent, Vec<Arc<dyn Fn(&Timer) + Send + Sync>>>,
+    // a kind from the config file, such as "meeting", counted like its timer type
+    kind: Option<String>,
+    // what the timer is spent on, e.g. "PR review #123"
+    label: Option<String>,
+    // the id of the task from the task list the timer is spent on, finishing it credits the task
+    task: Option<u64>,
+    interruptions: Vec<Interruption>,
+    // how long the system was last suspended, for the suspend and wake events
+    last_suspend: Option<Duration>,
+    // the pomodoro the timer belongs to, counting from 1
+    cycle: u64,
+    event_handlers: HashMap<TimerEvent, Vec<EventHandler>>,
 }
 
 impl Timer {
+    // Timers run on tokio's clock, so in paused time (`tokio::time::pause` or
+    // `#[tokio::test(start_paused = true)]`) they finish as soon as the clock gets there, without
+    // waiting. That goes for the controller and auto mode as well.
     pub fn new(timer_type: TimerType, duration: &Duration) -> Arc<Mutex<Timer>> {
         Arc::new(Mutex::new(Timer {
+            duration: *duration,
             event_handlers: HashMap::new(),
             handle: None,
+            generation: 0,
             last_started_at: None,
-            remaining: duration.clone(),
+            remaining: *duration,
             state: TimerState::Stopped,
             timer_type,
+            kind: None,
+            label: None,
+            task: None,
+            interruptions: Vec::new(),
+            last_suspend: None,
+            cycle: 1,
+        }))
+    }
+
+    // a restored timer is never running, call start to resume it
+    pub fn restore(snapshot: &TimerSnapshot) -> Arc<Mutex<Timer>> {
+        let state = match snapshot.state {
+            TimerState::Running | TimerState::Paused => TimerState::Paused,
+            TimerState::Stopped => TimerState::Stopped,
+        };
+
+        Arc::new(Mutex::new(Timer {
+            duration: snapshot.duration,
+            event_handlers: HashMap::new(),
+            handle: None,
+            generation: 0,
+            last_started_at: None,
+            remaining: snapshot.remaining,
+            state,
+            timer_type: snapshot.timer_type,
+            kind: snapshot.kind.clone(),
+            label: snapshot.label.clone(),
+            task: snapshot.task,
+            interruptions: snapshot.interruptions.clone(),
+            last_suspend: None,
+            cycle: 1,
         }))
     }
 
+    pub fn snapshot(&self) -> TimerSnapshot {
+        TimerSnapshot {
+            timer_type: self.timer_type,
+            state: self.state,
+            duration: self.duration,
+            remaining: self.time_left(),
+            kind: self.kind.clone(),
+            label: self.label.clone(),
+            task: self.task,
+            interruptions: self.interruptions.clone(),
+        }
+    }
+
     pub fn start(timer: &Arc<Mutex<Timer>>) {
         let timer = Arc::clone(timer);
 
         let mut timer_guard = timer.lock().expect("Failed to lock timer");
 
-        if let TimerState::Running = timer_guard.state {
+        let event = match timer_guard.state {
+            TimerState::Running => return,
+            TimerState::Paused => TimerEvent::Resume,
+            TimerState::Stopped => TimerEvent::Start,
+        };
+
+        Timer::schedule(&timer, &mut timer_guard);
+
+        timer_guard.event(event);
+    }
+
+    // change the time left, a running timer keeps running towards its new end and a running or
+    // paused one that has no time left finishes right away. A finished timer that gets more time
+    // runs again.
+    pub fn adjust(timer: &Arc<Mutex<Timer>>, adjustment: Adjustment, amount: Duration) {
+        let mut timer_guard = timer.lock().expect("Failed to lock timer");
+        let was_finished = timer_guard.is_finished();
+
+        let time_left = timer_guard.time_left();
+        let elapsed = timer_guard.duration.saturating_sub(time_left);
+
+        let remaining = match adjustment {
+            Adjustment::Add => time_left + amount,
+            Adjustment::Subtract => time_left.saturating_sub(amount),
+            Adjustment::Set => amount,
+        };
+
+        // the time that has already passed stays part of the timer
+        timer_guard.duration = elapsed + remaining;
+        timer_guard.remaining = remaining;
+
+        // a paused timer without time left finishes as well
+        let finishes = remaining.is_zero() && timer_guard.state != TimerState::Stopped;
+
+        let resumes = was_finished && !remaining.is_zero();
+
+        if finishes {
+            timer_guard.abort_current_task();
+        } else if resumes || timer_guard.state == TimerState::Running {
+            // replace the sleep task with one that ends at the new time
+            timer_guard.abort_current_task();
+            Timer::schedule(timer, &mut timer_guard);
+        }
+
+        timer_guard.event(TimerEvent::Adjust);
+
+        if finishes {
+            timer_guard.finished();
+        } else if resumes {
+            timer_guard.event(TimerEvent::Resume);
+        }
+    }
+
+    // The system was suspended for `gap`, which the monotonic clock the timer runs on didn't see.
+    // A running timer counts it as elapsed, is paused or finishes, depending on the policy. The
+    // suspend is only noticed after waking up, so both events are sent then, one after the other.
+    // Timers that weren't running are left alone.
+    pub fn suspended(timer: &Arc<Mutex<Timer>>, gap: Duration, policy: SuspendPolicy) {
+        let mut timer_guard = timer.lock().expect("Failed to lock timer");
+
+        if timer_guard.state != TimerState::Running {
             return;
         }
 
-        let duration = timer_guard.time_left();
+        timer_guard.last_suspend = Some(gap);
+        timer_guard.event(TimerEvent::Suspend);
+        timer_guard.event(TimerEvent::Wake);
 
-        let timer2 = Arc::clone(&timer);
+        match policy {
+            SuspendPolicy::Count => {
+                let remaining = timer_guard.time_left().saturating_sub(gap);
 
-        let handle = task::spawn(async move {
-            tokio::time::sleep(duration).await;
+                timer_guard.abort_current_task();
+                timer_guard.remaining = remaining;
 
-            let mut timer_guard = timer2.lock().expect("Failed to lock timer");
-            timer_guard.finished();
-        });
+                // the suspend may have outlasted the timer
+                if remaining.is_zero() {
+                    timer_guard.finished();
+                } else {
+                    Timer::schedule(timer, &mut timer_guard);
+                }
+            }
+            SuspendPolicy::Pause => timer_guard.pause(),
+            SuspendPolicy::Finish => {
+                timer_guard.abort_current_task();
+                timer_guard.finished();
+            }
+        }
+    }
 
-        timer_guard.handle = Some(handle);
-        timer_guard.last_started_at = Some(Instant::now());
-        timer_guard.state = TimerState::Running;
+    pub fn interrupt(&mut self, interruption: Interruption) {
+        self.interruptions.push(interruption);
 
-        timer_guard.event(TimerEvent::Start);
+        self.event(TimerEvent::Interrupt);
     }
 
     pub fn stop(&mut self) {
-        self.state = TimerState::Stopped;
-
-        // abort current sleep task
-        self.abort_current_task();
+        self.halt();
 
         self.event(TimerEvent::Stop);
     }
 
+    // like stop, but for a timer that is abandoned in favour of the next one
+    pub fn skip(&mut self) {
+        self.halt();
+
+        self.event(TimerEvent::Skip);
+    }
+
     pub fn pause(&mut self) {
         // it's only possible to pause a timer that's running
         if let TimerState::Running = self.state {
@@ -124,7 +417,7 @@ impl Timer {
         }
     }
 
-    pub fn on(&mut self, event: TimerEvent, callback: Arc<dyn Fn(&Timer) + Send + Sync>) {
+    pub fn on(&mut self, event: TimerEvent, callback: EventHandler) {
         self.event_handlers
             .entry(event)
             .or_default()
@@ -132,18 +425,122 @@ impl Timer {
     }
 
     pub fn timer_type(&self) -> TimerType {
-        self.timer_type.clone()
+        self.timer_type
     }
 
-    pub fn clone(&self) -> Timer {
-        Timer {
-            handle: None,
-            last_started_at: self.last_started_at.clone(),
-            remaining: self.remaining.clone(),
-            state: self.state.clone(),
-            timer_type: self.timer_type.clone(),
-            event_handlers: HashMap::new(),
+    pub fn is_long_break(&self) -> bool {
+        self.timer_type == TimerType::LongBreak
+    }
+
+    pub fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    pub fn set_kind(&mut self, kind: Option<String>) {
+        self.kind = kind;
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    pub fn task(&self) -> Option<u64> {
+        self.task
+    }
+
+    pub fn set_task(&mut self, task: Option<u64>) {
+        self.task = task;
+    }
+
+    pub fn interruptions(&self) -> Interruptions {
+        let mut counts = Interruptions::default();
+
+        for interruption in &self.interruptions {
+            match interruption.kind {
+                InterruptionKind::Internal => counts.internal += 1,
+                InterruptionKind::External => counts.external += 1,
+            }
         }
+
+        counts
+    }
+
+    pub fn last_interruption(&self) -> Option<&Interruption> {
+        self.interruptions.last()
+    }
+
+    pub fn last_suspend(&self) -> Option<Duration> {
+        self.last_suspend
+    }
+
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    pub fn set_cycle(&mut self, cycle: u64) {
+        self.cycle = cycle;
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.state
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    // for timers that haven't started yet, running timers are changed with `adjust`
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+        self.remaining = duration;
+    }
+
+    // stopped without any of its time having run
+    pub fn is_new(&self) -> bool {
+        self.state == TimerState::Stopped && self.remaining == self.duration
+    }
+
+    // stopped because the time ran out rather than by the user
+    pub fn is_finished(&self) -> bool {
+        self.state == TimerState::Stopped && self.time_left().is_zero()
+    }
+
+    // run the timer until its remaining time is up
+    fn schedule(timer: &Arc<Mutex<Timer>>, timer_guard: &mut Timer) {
+        let duration = timer_guard.remaining;
+
+        timer_guard.generation += 1;
+        let generation = timer_guard.generation;
+
+        let timer = Arc::clone(timer);
+
+        let handle = task::spawn(async move {
+            tokio::time::sleep(duration).await;
+
+            let mut timer_guard = timer.lock().expect("Failed to lock timer");
+
+            // aborting the task doesn't stop one that is waiting for the lock
+            if timer_guard.generation == generation && timer_guard.state == TimerState::Running {
+                timer_guard.finished();
+            }
+        });
+
+        timer_guard.handle = Some(handle);
+        timer_guard.last_started_at = Some(Instant::now());
+        timer_guard.state = TimerState::Running;
+    }
+
+    fn halt(&mut self) {
+        // keep the remaining time so it can still be reported after stopping
+        self.remaining = self.time_left();
+        self.state = TimerState::Stopped;
+
+        // abort current sleep task
+        self.abort_current_task();
     }
 
     fn abort_current_task(&self) {
@@ -162,12 +559,33 @@ impl Timer {
     fn event(&self, event: TimerEvent) {
         if let Some(handlers) = self.event_handlers.get(&event) {
             for callback in handlers {
-                callback(&*self);
+                callback(self);
             }
         }
     }
 }
 
+impl Clone for Timer {
+    fn clone(&self) -> Timer {
+        Timer {
+            duration: self.duration,
+            handle: None,
+            generation: 0,
+            last_started_at: self.last_started_at,
+            remaining: self.remaining,
+            state: self.state,
+            timer_type: self.timer_type,
+            kind: self.kind.clone(),
+            label: self.label.clone(),
+            task: self.task,
+            interruptions: self.interruptions.clone(),
+            last_suspend: self.last_suspend,
+            cycle: self.cycle,
+            event_handlers: HashMap::new(),
+        }
+    }
+}
+
 // impl Drop for Timer {
 //     fn drop(&mut self) {
 //         println!("Timer dropped");
@@ -0,0 +1,273 @@
+use crate::client::{Client, ClientError};
+use crate::history::{History, Stats};
+use crate::protocol::{Command, ErrorCode, Reply};
+use crate::status::{self, Status};
+use crate::timer::{Adjustment, InterruptionKind, TimerState, TimerType};
+use chrono::Local;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::{Duration, Instant};
+
+// how often the status is fetched from pomo
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+// the history log is read less often than the status
+const HISTORY_INTERVAL: Duration = Duration::from_secs(5);
+
+// how much time the extend key adds
+const EXTEND_BY: Duration = Duration::from_secs(5 * 60);
+
+// a full-screen view of a running timer, any number of them can watch the same timer. `run` puts
+// it in the terminal, tests draw it with `draw` and press keys with `key` instead.
+pub struct Ui {
+    client: Client,
+    session: Option<String>,
+    status: Status,
+    completed_today: u64,
+    history_read_at: Instant,
+    message: Option<String>,
+}
+
+// returns once the user quits or the timer is stopped
+pub fn run(client: Client, session: Option<String>) -> Result<(), ClientError> {
+    let mut ui = Ui::new(client, session)?;
+
+    let mut terminal = ratatui::try_init()?;
+    let result = ui.run(&mut terminal);
+    ratatui::restore();
+
+    match result {
+        Err(error) if timer_stopped(&error) => Ok(()),
+        result => result,
+    }
+}
+
+// whether the error only means the timer was stopped, from here or elsewhere. Pomo hangs up once
+// its last timer is stopped, which may happen while a request is on its way.
+pub fn timer_stopped(error: &ClientError) -> bool {
+    match error {
+        ClientError::Closed => true,
+        ClientError::Daemon(error) => error.code == ErrorCode::SessionNotFound,
+        ClientError::Io(error) => matches!(
+            error.kind(),
+            io::ErrorKind::BrokenPipe | io::ErrorKind::ConnectionReset
+        ),
+        _ => false,
+    }
+}
+
+impl Ui {
+    pub fn new(mut client: Client, session: Option<String>) -> Result<Ui, ClientError> {
+        let status = fetch_status(&mut client)?;
+
+        let mut ui = Ui {
+            client,
+            session,
+            status,
+            completed_today: 0,
+            history_read_at: Instant::now(),
+            message: None,
+        };
+
+        ui.read_history();
+
+        Ok(ui)
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), ClientError> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(REFRESH_INTERVAL)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        match key.code {
+                            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                            code => self.key(code)?,
+                        }
+                    }
+                }
+            }
+
+            self.refresh()?;
+        }
+    }
+
+    // fetches the status, and now and then the pomodoros completed today
+    pub fn refresh(&mut self) -> Result<(), ClientError> {
+        self.status = fetch_status(&mut self.client)?;
+
+        if self.history_read_at.elapsed() >= HISTORY_INTERVAL {
+            self.read_history();
+        }
+
+        Ok(())
+    }
+
+    // errors from pomo, such as interrupting a break, are shown in the footer
+    pub fn key(&mut self, code: KeyCode) -> Result<(), ClientError> {
+        let finished = self.status.state == TimerState::Stopped && self.status.remaining_secs == 0;
+
+        let command = match code {
+            KeyCode::Char(' ') if finished => Command::Next,
+            KeyCode::Char(' ') if self.status.state == TimerState::Running => Command::Pause,
+            KeyCode::Char(' ') => Command::Resume,
+            KeyCode::Char('p') => Command::Pause,
+            KeyCode::Char('r') => Command::Resume,
+            KeyCode::Char('n') => Command::Next,
+            KeyCode::Char('s') => Command::Stop,
+            KeyCode::Char('i') => Command::Interrupt {
+                kind: InterruptionKind::Internal,
+                note: None,
+                pause: false,
+            },
+            KeyCode::Char('e') => Command::Adjust {
+                adjustment: Adjustment::Add,
+                duration: EXTEND_BY,
+            },
+            _ => return Ok(()),
+        };
+
+        self.message = match self.client.request(command) {
+            Ok(_) => None,
+            Err(ClientError::Daemon(error)) => Some(error.message),
+            Err(err) => return Err(err),
+        };
+
+        // e.g. a skipped or finished timer changes the counts
+        self.read_history();
+
+        Ok(())
+    }
+
+    fn read_history(&mut self) {
+        self.history_read_at = Instant::now();
+
+        let Ok(entries) = History::default().entries() else {
+            return;
+        };
+
+        let entries: Vec<_> = entries
+            .into_iter()
+            .filter(|entry| {
+                self.session
+                    .as_ref()
+                    .is_none_or(|name| &entry.session == name)
+            })
+            .collect();
+
+        self.completed_today = Stats::compute(&entries, Local::now().date_naive())
+            .today
+            .pomodoros;
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let status = &self.status;
+
+        let color = match status.timer_type {
+            TimerType::Work => Color::Red,
+            TimerType::Break => Color::Green,
+            TimerType::LongBreak => Color::Blue,
+        };
+
+        let title = match &self.session {
+            Some(session) => format!(" pomo · {} ", session),
+            None => " pomo ".to_string(),
+        };
+
+        let block = Block::bordered().title(title.bold());
+        let area = block.inner(frame.area());
+        frame.render_widget(block, frame.area());
+
+        let [heading, gauge, details, _, footer] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let heading_text = vec![
+            Line::from(vec![
+                status.title().fg(color).bold(),
+                format!("  {}", status.state).into(),
+            ]),
+            Line::from(status.label.clone().unwrap_or_default().italic()),
+        ];
+        frame.render_widget(Paragraph::new(heading_text).centered(), heading);
+
+        let ratio = if status.total_secs == 0 {
+            1.0
+        } else {
+            status.elapsed_secs as f64 / status.total_secs as f64
+        };
+
+        let label = format!(
+            "{} / {}",
+            status::format_clock(status.remaining_secs),
+            status::format_clock(status.total_secs)
+        );
+
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered())
+                .gauge_style(Style::default().fg(color))
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(label),
+            gauge,
+        );
+
+        let details_text = vec![
+            Line::from(cycle_position(status)),
+            Line::from(format!(
+                "Next: {} for {}",
+                status.next_timer_type,
+                status::format_clock(status.next_duration_secs)
+            )),
+            Line::from(format!("Completed today: {}", self.completed_today)),
+            Line::from(format!(
+                "Interruptions: {} internal, {} external",
+                status.interruptions.internal, status.interruptions.external
+            )),
+        ];
+        frame.render_widget(Paragraph::new(details_text), details);
+
+        let footer_text = match &self.message {
+            Some(message) => Line::from(message.as_str().yellow()),
+            None => Line::from(
+                "space pause/resume · n next · e +5 min · i interrupt · s stop · q quit"
+                    .dark_gray(),
+            ),
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+}
+
+fn fetch_status(client: &mut Client) -> Result<Status, ClientError> {
+    match client.request(Command::Status)? {
+        Reply::Status(status) => Ok(*status),
+        _ => Err(ClientError::InvalidResponse),
+    }
+}
+
+fn cycle_position(status: &Status) -> String {
+    if let Some(sequence) = status.sequence {
+        let end = if sequence.repeat { "" } else { ", then done" };
+        return format!("Step {} of {}{}", sequence.step, sequence.steps, end);
+    }
+
+    // an interval of 0 means there are no long breaks
+    match status.cycle_position {
+        Some(position) => format!(
+            "Pomodoro {} of {} until the long break",
+            position, status.long_break_interval
+        ),
+        None => format!("Pomodoro {}", status.cycle),
+    }
+}
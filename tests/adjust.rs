@@ -0,0 +1,107 @@
+mod common;
+
+use common::{config, record};
+use pomo_cli::controller::Controller;
+use pomo_cli::timer::{Adjustment, TimerEvent, TimerState, TimerType};
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn add_time_to_running_timer() {
+    let controller = Controller::new(config(Duration::from_millis(300)));
+    let events = record(&controller, &TimerEvent::ALL);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    Controller::adjust(&controller, Adjustment::Add, Duration::from_secs(60));
+
+    // the old sleep task would have finished the timer by now
+    tokio::time::sleep(Duration::from_millis(600)).await;
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Running);
+    assert_eq!(status.total_secs, 60);
+    assert_eq!(
+        events.drain().map(|entry| entry.event).collect::<Vec<_>>(),
+        vec![TimerEvent::Start, TimerEvent::Adjust]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn subtract_finishes_running_timer() {
+    let controller = Controller::new(config(Duration::from_secs(60)));
+    let events = record(&controller, &TimerEvent::ALL);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    Controller::adjust(&controller, Adjustment::Subtract, Duration::from_secs(120));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Stopped);
+    assert_eq!(status.remaining_secs, 0);
+    assert_eq!(
+        events.drain().map(|entry| entry.event).collect::<Vec<_>>(),
+        vec![TimerEvent::Start, TimerEvent::Adjust, TimerEvent::Finish]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn set_time_on_paused_timer() {
+    let controller = Controller::new(config(Duration::from_secs(60)));
+    Controller::start(&controller).expect("Failed to start timer");
+    Controller::pause(&controller);
+
+    Controller::adjust(&controller, Adjustment::Set, Duration::from_secs(10 * 60));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Paused);
+    assert_eq!(status.remaining_secs, 10 * 60);
+}
+
+#[tokio::test(start_paused = true)]
+async fn subtract_finishes_paused_timer() {
+    let controller = Controller::new(config(Duration::from_secs(60)));
+    let events = record(&controller, &TimerEvent::ALL);
+    Controller::start(&controller).expect("Failed to start timer");
+    Controller::pause(&controller);
+
+    Controller::adjust(&controller, Adjustment::Subtract, Duration::from_secs(120));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Stopped);
+    assert_eq!(status.remaining_secs, 0);
+    assert_eq!(
+        events.drain().map(|entry| entry.event).collect::<Vec<_>>(),
+        vec![
+            TimerEvent::Start,
+            TimerEvent::Pause,
+            TimerEvent::Adjust,
+            TimerEvent::Finish
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn add_time_to_finished_timer() {
+    let controller = Controller::new(config(Duration::from_millis(100)));
+    let events = record(&controller, &TimerEvent::ALL);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    Controller::adjust(&controller, Adjustment::Add, Duration::from_secs(60));
+
+    // keep going with the same timer rather than starting the break
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Work);
+    assert_eq!(status.state, TimerState::Running);
+    assert_eq!(status.remaining_secs, 60);
+
+    // the timer resumes, it doesn't start over
+    assert_eq!(
+        events.drain().map(|entry| entry.event).collect::<Vec<_>>(),
+        vec![
+            TimerEvent::Start,
+            TimerEvent::Finish,
+            TimerEvent::Adjust,
+            TimerEvent::Resume
+        ]
+    );
+}
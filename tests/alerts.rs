@@ -0,0 +1,157 @@
+mod common;
+
+use common::{config, temp_dir};
+use pomo_cli::alerts::{
+    AlertConfig, AlertKind, Alerter, AudioBackend, Backend, CommandBackend, NullBackend, Played,
+    Sound,
+};
+use pomo_cli::controller::{Config, Controller};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn write_wav(name: &str, samples: &[i16]) -> PathBuf {
+    let path = temp_dir(&format!("sound-{}", name)).join("alert.wav");
+
+    let sound = Sound {
+        channels: 1,
+        sample_rate: 8000,
+        samples: samples.to_vec(),
+    };
+    std::fs::write(&path, sound.to_wav()).expect("Failed to write sound");
+
+    path
+}
+
+fn alerts(work_end: Option<PathBuf>) -> AlertConfig {
+    AlertConfig {
+        backend: Backend::Null,
+        work_end,
+        ..AlertConfig::default()
+    }
+}
+
+#[test]
+fn load_wav_with_volume() {
+    let path = write_wav("volume", &[1000, -1000, 32000]);
+
+    let sound = Sound::load(&path).expect("Failed to load sound");
+    assert_eq!(sound.samples, vec![1000, -1000, 32000]);
+    assert_eq!(sound.with_volume(0.5).samples, vec![500, -500, 16000]);
+
+    // volume is capped, alerts never get louder than the file
+    assert_eq!(sound.with_volume(3.0).samples, sound.samples);
+
+    std::fs::remove_file(path).unwrap_or(());
+}
+
+#[test]
+fn unsupported_sound_file() {
+    let config = alerts(Some(PathBuf::from("alert.mp3")));
+
+    assert!(Alerter::new(config, Arc::new(NullBackend::default())).is_err());
+}
+
+#[test]
+fn bell_without_sound() {
+    let backend = Arc::new(NullBackend::default());
+    let alerter = Alerter::new(alerts(None), backend.clone()).expect("Failed to create alerter");
+
+    alerter.alert(AlertKind::LongBreakEnd);
+
+    assert_eq!(backend.played(), vec![Played::Bell]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn play_sound_when_work_ends() {
+    let path = write_wav("finish", &[100, 200]);
+    let backend = Arc::new(NullBackend::default());
+    let alerter = Arc::new(
+        Alerter::new(alerts(Some(path.clone())), backend.clone())
+            .expect("Failed to create alerter"),
+    );
+
+    let controller = Controller::new(config(Duration::from_millis(100)));
+    alerter.watch(&controller);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // sounds are played on a thread of their own, which paused time doesn't wait for
+    let started_at = std::time::Instant::now();
+    while backend.played().is_empty() && started_at.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let played = backend.played();
+    assert_eq!(played.len(), 1);
+    assert!(matches!(&played[0], Played::Sound(sound) if sound.samples == vec![100, 200]));
+
+    std::fs::remove_file(path).unwrap_or(());
+}
+
+#[tokio::test(start_paused = true)]
+async fn repeat_until_next_timer() {
+    let backend = Arc::new(NullBackend::default());
+    let config = AlertConfig {
+        repeat: true,
+        repeat_interval: Duration::from_secs(1),
+        ..alerts(None)
+    };
+    let alerter =
+        Arc::new(Alerter::new(config, backend.clone()).expect("Failed to create alerter"));
+
+    let controller = Controller::new(config_with_break(Duration::from_millis(100)));
+    alerter.watch(&controller);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    // the first alert and one repetition
+    tokio::time::sleep(Duration::from_millis(1600)).await;
+    assert_eq!(backend.played().len(), 2);
+
+    // starting the break acknowledges the alert
+    Controller::next(&controller).expect("Failed to start next timer");
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(backend.played().len(), 2);
+}
+
+fn config_with_break(work_duration: Duration) -> Config {
+    Config {
+        break_duration: Duration::from_secs(60),
+        ..config(work_duration)
+    }
+}
+
+#[test]
+fn sounds_played_at_the_same_time() {
+    let output = temp_dir("played").join("sounds");
+
+    // the player is slow to read the file, so both sounds are in their files at once
+    let backend = Arc::new(CommandBackend::new(vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "sleep 0.3; cat \"$0\" >> \"$1\"".to_string(),
+        "{file}".to_string(),
+        output.display().to_string(),
+    ]));
+
+    let sounds = [vec![1; 10], vec![2; 20]].map(|samples| Sound {
+        channels: 1,
+        sample_rate: 8000,
+        samples,
+    });
+
+    let players = sounds.clone().map(|sound| {
+        let backend = Arc::clone(&backend);
+        std::thread::spawn(move || backend.play(&sound))
+    });
+    for player in players {
+        player.join().unwrap().expect("Failed to play sound");
+    }
+
+    let played = std::fs::read(&output).expect("Failed to read played sounds");
+    let [first, second] = sounds.map(|sound| sound.to_wav());
+    assert!(
+        played == [first.clone(), second.clone()].concat() || played == [second, first].concat()
+    );
+}
@@ -0,0 +1,174 @@
+// Runs the pomo binary in the background, with its config, state and socket in a directory of
+// the test's own.
+mod common;
+
+use common::temp_dir;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+struct Pomo {
+    dir: PathBuf,
+}
+
+impl Pomo {
+    fn new(name: &str) -> Pomo {
+        Pomo {
+            dir: temp_dir(&format!("background-{}", name)),
+        }
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_pomo"));
+        command
+            .args(args)
+            .env("XDG_CONFIG_HOME", self.dir.join("config"))
+            .env("XDG_DATA_HOME", self.dir.join("data"))
+            .env("XDG_STATE_HOME", self.dir.join("state"))
+            .env("POMO_SOCKET", self.dir.join("pomo.sock"))
+            .env_remove("POMO_DAEMON");
+        command
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("Failed to run pomo")
+    }
+
+    fn stdout(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "pomo {:?} failed: {:?}",
+            args,
+            output
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn pid_file(&self) -> PathBuf {
+        self.dir.join("state").join("pomo").join("pomo.pid")
+    }
+
+    // waits for the daemon to exit and clean up after itself
+    fn wait_for_exit(&self) {
+        let started_at = Instant::now();
+
+        while self.pid_file().exists() || self.dir.join("pomo.sock").exists() {
+            assert!(
+                started_at.elapsed() < Duration::from_secs(5),
+                "Pomo didn't exit"
+            );
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+#[test]
+fn daemon_flag_with_pomo_running() {
+    let pomo = Pomo::new("running");
+
+    let output = pomo.stdout(&["start", "-D", "--no-notifications", "-d", "5m"]);
+    assert!(output.starts_with("Pomo is running in the background"));
+
+    // the second timer is started in the running pomo, which says so
+    let output = pomo.stdout(&[
+        "start",
+        "-D",
+        "--name",
+        "review",
+        "--no-notifications",
+        "-d",
+        "10m",
+    ]);
+    assert_eq!(
+        output,
+        "Pomo is already running in the background, the timer 'review' was started there.\n"
+    );
+
+    let output = pomo.stdout(&["list"]);
+    assert!(output.contains("default"));
+    assert!(output.contains("review"));
+
+    pomo.stdout(&["stop", "--name", "review"]);
+    pomo.stdout(&["stop"]);
+    pomo.wait_for_exit();
+}
+
+#[test]
+fn spawn_attach_and_exit() {
+    let pomo = Pomo::new("lifecycle");
+
+    // the start hook records whether it sees the marker of the background process
+    let hooks = pomo.dir.join("config").join("pomo").join("hooks");
+    std::fs::create_dir_all(&hooks).expect("Failed to create hooks directory");
+    let hook = hooks.join("start.sh");
+    std::fs::write(
+        &hook,
+        format!(
+            "#!/bin/sh\necho \"${{POMO_DAEMON:-unset}}\" > {}\n",
+            pomo.dir.join("hook-env").display()
+        ),
+    )
+    .expect("Failed to write hook");
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))
+        .expect("Failed to make hook executable");
+
+    let output = pomo.stdout(&["start", "-D", "--no-notifications", "-d", "5m"]);
+
+    // the pidfile holds the pid of the background process
+    let pid = std::fs::read_to_string(pomo.pid_file()).expect("Failed to read pidfile");
+    assert!(output.contains(&format!("(pid {})", pid)));
+
+    let started_at = Instant::now();
+    let hook_env = loop {
+        if let Ok(env) = std::fs::read_to_string(pomo.dir.join("hook-env")) {
+            break env;
+        }
+        assert!(
+            started_at.elapsed() < Duration::from_secs(5),
+            "The start hook didn't run"
+        );
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert_eq!(hook_env, "unset\n");
+
+    let mut attach = pomo
+        .command(&["attach"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to run pomo attach");
+
+    // attach shows the status as soon as it is subscribed
+    let mut stdout = attach.stdout.take().unwrap();
+    let mut status = [0; 64];
+    assert!(stdout.read(&mut status).expect("Failed to read status") > 0);
+
+    pomo.stdout(&["stop"]);
+
+    let mut rest = String::new();
+    stdout
+        .read_to_string(&mut rest)
+        .expect("Failed to read output");
+    assert!(attach.wait().expect("Failed to wait for attach").success());
+    assert!(rest.ends_with("Pomo has stopped.\n"));
+
+    pomo.wait_for_exit();
+}
+
+#[test]
+fn errors_go_to_stderr() {
+    let pomo = Pomo::new("errors");
+
+    // nothing is running yet
+    let output = pomo.run(&["status"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to connect to socket"));
+
+    let output = pomo.run(&["start", "--name", "no/such"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(!output.stderr.is_empty());
+}
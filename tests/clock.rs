@@ -0,0 +1,163 @@
+// Timers run on tokio's clock, so in paused time these tests go through hours of timers in
+// milliseconds.
+mod common;
+
+use chrono::{Local, TimeZone};
+use common::{minutes, record, SimulatedClock};
+use pomo_cli::controller::{Config, Controller, NotStarted};
+use pomo_cli::history::{Entry, Stats};
+use pomo_cli::timer::{Adjustment, Timer, TimerEvent, TimerState, TimerType};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+#[tokio::test(start_paused = true)]
+async fn pomodoro_finishes_without_waiting() {
+    let controller = Controller::new(Config::default());
+    let started_at = std::time::Instant::now();
+
+    Controller::start(&controller).expect("Failed to start timer");
+
+    sleep(minutes(24)).await;
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Running);
+    assert_eq!(status.remaining_secs, 60);
+
+    // a second past the end, the test and the timer would wake up in either order at the same time
+    sleep(minutes(1) + Duration::from_secs(1)).await;
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Stopped);
+    assert_eq!(status.remaining_secs, 0);
+    assert_eq!(status.elapsed_secs, 25 * 60);
+
+    // without auto mode the break waits
+    sleep(minutes(60)).await;
+    assert_eq!(Controller::status(&controller).timer_type, TimerType::Work);
+
+    assert!(started_at.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn paused_time_is_not_counted() {
+    let controller = Controller::new(Config::default());
+    Controller::start(&controller).expect("Failed to start timer");
+
+    sleep(minutes(10)).await;
+    Controller::pause(&controller);
+    sleep(minutes(60)).await;
+    Controller::resume(&controller);
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.remaining_secs, 15 * 60);
+
+    Controller::adjust(&controller, Adjustment::Add, minutes(5));
+    sleep(minutes(19)).await;
+    assert_eq!(Controller::status(&controller).remaining_secs, 60);
+
+    sleep(minutes(1) + Duration::from_secs(1)).await;
+    assert_eq!(Controller::status(&controller).state, TimerState::Stopped);
+}
+
+#[tokio::test(start_paused = true)]
+async fn auto_mode_runs_whole_cycles() {
+    let controller = Controller::new(Config {
+        auto: true,
+        ..Config::default()
+    });
+    let starts = record(&controller, &[TimerEvent::Start]);
+
+    Controller::start(&controller).expect("Failed to start timer");
+
+    // four pomodoros, three short breaks and a long break take 130 minutes
+    sleep(minutes(3 * 130 + 1)).await;
+
+    let types = starts
+        .drain()
+        .map(|entry| entry.timer_type)
+        .collect::<Vec<_>>();
+    assert_eq!(types.len(), 3 * 8 + 1);
+    assert_eq!(
+        &types[..8],
+        [
+            TimerType::Work,
+            TimerType::Break,
+            TimerType::Work,
+            TimerType::Break,
+            TimerType::Work,
+            TimerType::Break,
+            TimerType::Work,
+            TimerType::LongBreak,
+        ]
+    );
+    assert_eq!(types[..8], types[8..16]);
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Work);
+    assert_eq!(status.remaining_secs, 24 * 60);
+    assert_eq!((status.completed, status.skipped), (12, 0));
+    assert_eq!(status.cycle_position, Some(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn auto_mode_stops_at_the_end_of_a_sequence() {
+    let controller = Controller::new(Config {
+        auto: true,
+        sequence: Some(
+            "work 50m, break 10m, stop"
+                .parse()
+                .expect("Failed to parse sequence"),
+        ),
+        ..Config::default()
+    });
+
+    Controller::start(&controller).expect("Failed to start timer");
+    sleep(minutes(8 * 60)).await;
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Break);
+    assert_eq!(status.state, TimerState::Stopped);
+    assert_eq!(status.completed, 1);
+
+    assert_eq!(
+        Controller::next(&controller),
+        Err(NotStarted::SequenceComplete)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_of_simulated_days() {
+    let controller = Controller::new(Config {
+        auto: true,
+        ..Config::default()
+    });
+
+    // a Monday at midnight
+    let clock = SimulatedClock::new(Local.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap());
+    let (tx, rx) = flume::unbounded();
+
+    Controller::on(
+        &controller,
+        TimerEvent::Finish,
+        Arc::new(move |timer: &Timer| {
+            let mut entry = Entry::new("default", TimerEvent::Finish, timer);
+            entry.time = clock.now();
+            let _ = tx.send(entry);
+        }),
+    );
+
+    Controller::start(&controller).expect("Failed to start timer");
+
+    // until a minute before Thursday
+    sleep(minutes(3 * 24 * 60 - 1)).await;
+
+    let entries = rx.drain().collect::<Vec<_>>();
+    let today = clock.now().date_naive();
+    assert_eq!(
+        today,
+        chrono::NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()
+    );
+
+    let stats = Stats::compute(&entries, today);
+    assert_eq!(stats.today.pomodoros, 45);
+    assert_eq!(stats.week.pomodoros, 133);
+    assert_eq!(stats.current_streak, 3);
+}
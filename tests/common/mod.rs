@@ -0,0 +1,138 @@
+// Helpers shared by the tests. Every test file only uses some of them.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Local};
+use pomo_cli::client::{Client, ClientError};
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::daemon::Daemon;
+use pomo_cli::history::Entry;
+use pomo_cli::protocol::{Command, Reply};
+use pomo_cli::status::Status;
+use pomo_cli::timer::{Timer, TimerEvent};
+use std::fs;
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Once};
+use std::time::Duration;
+
+pub fn minutes(minutes: u64) -> Duration {
+    Duration::from_secs(minutes * 60)
+}
+
+// the default config with another work duration
+pub fn config(work_duration: Duration) -> Config {
+    Config {
+        work_duration,
+        ..Config::default()
+    }
+}
+
+// an empty directory of its own for the test, e.g. for hooks or state files
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pomo-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("Failed to create temporary dir");
+    dir
+}
+
+// history entries of the events from now on, as they would be written to the history file
+pub fn record(
+    controller: &Arc<Mutex<Controller>>,
+    events: &[TimerEvent],
+) -> flume::Receiver<Entry> {
+    let (tx, rx) = flume::unbounded();
+
+    for &event in events {
+        let tx = tx.clone();
+        Controller::on(
+            controller,
+            event,
+            Arc::new(move |timer: &Timer| {
+                let _ = tx.send(Entry::new("default", event, timer));
+            }),
+        );
+    }
+
+    rx
+}
+
+// A wall clock that moves along with tokio's clock from `start` on. Timers run on tokio's clock,
+// which tests can pause and advance, while history entries are stamped with the system's wall
+// clock. Setting `entry.time` to this one instead makes simulated timers land on simulated dates.
+#[derive(Clone, Copy, Debug)]
+pub struct SimulatedClock {
+    start: DateTime<Local>,
+    started_at: tokio::time::Instant,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Local>) -> SimulatedClock {
+        SimulatedClock {
+            start,
+            started_at: tokio::time::Instant::now(),
+        }
+    }
+
+    pub fn now(&self) -> DateTime<Local> {
+        self.start + chrono::Duration::from_std(self.started_at.elapsed()).unwrap_or_default()
+    }
+}
+
+// A daemon serving a socket of its own, driven like the pomo commands do. The timers run on
+// tokio's clock, while the daemon answers each connection on a thread of its own.
+pub struct TestDaemon {
+    pub socket: PathBuf,
+    pub shutdown: flume::Receiver<()>,
+}
+
+impl TestDaemon {
+    pub fn serve(name: &str) -> TestDaemon {
+        // sessions save their state and the UI reads the history, keep both out of the user's dirs
+        static DIRS: Once = Once::new();
+        DIRS.call_once(|| {
+            std::env::set_var("XDG_STATE_HOME", temp_dir("daemon-state"));
+            std::env::set_var("XDG_DATA_HOME", temp_dir("daemon-data"));
+        });
+
+        let socket = temp_dir(&format!("daemon-{}", name)).join("pomo.sock");
+        let listener = UnixListener::bind(&socket).expect("Failed to bind socket");
+
+        let (daemon, shutdown) = Daemon::new(Arc::new(|_, _| {}), Arc::new(|_, _| {}));
+        daemon.serve(listener);
+
+        TestDaemon { socket, shutdown }
+    }
+
+    pub fn client(&self, session: Option<&str>) -> Client {
+        Client::connect(&self.socket)
+            .expect("Failed to connect")
+            .with_session(session.map(String::from))
+    }
+
+    pub fn request(&self, session: Option<&str>, command: Command) -> Result<Reply, ClientError> {
+        self.client(session).request(command)
+    }
+
+    pub fn start(&self, session: &str) {
+        self.request(Some(session), start_command())
+            .expect("Failed to start timer");
+    }
+
+    pub fn status(&self, session: Option<&str>) -> Result<Status, ClientError> {
+        match self.request(session, Command::Status)? {
+            Reply::Status(status) => Ok(*status),
+            reply => panic!("Expected a status, got {:?}", reply),
+        }
+    }
+}
+
+// a new timer with the default config
+pub fn start_command() -> Command {
+    Command::Start {
+        config: Config::default(),
+        snapshot: None,
+        replace: false,
+        label: None,
+        task: None,
+    }
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::{minutes, temp_dir};
+use pomo_cli::config::{self, ConfigError, ConfigFile, Settings};
+use pomo_cli::controller::Config;
+use std::fs;
+
+#[test]
+fn later_settings_take_precedence() {
+    let file = Settings {
+        work_duration: Some(minutes(50)),
+        break_duration: Some(minutes(10)),
+        auto: Some(true),
+        ..Settings::default()
+    };
+    let cli = Settings {
+        work_duration: Some(minutes(30)),
+        auto: Some(false),
+        notifications: Some(false),
+        ..Settings::default()
+    };
+
+    let settings = file.merge(cli);
+    assert_eq!(settings.work_duration, Some(minutes(30)));
+    assert_eq!(settings.break_duration, Some(minutes(10)));
+    assert_eq!(settings.auto, Some(false));
+    assert_eq!(settings.long_break_interval, None);
+
+    // what isn't set keeps its default
+    let config = settings.apply(Config::default());
+    assert_eq!(config.work_duration, minutes(30));
+    assert_eq!(config.break_duration, minutes(10));
+    assert_eq!(config.long_break_duration, minutes(15));
+    assert_eq!(config.long_break_interval, 4);
+    assert!(!config.auto);
+    assert!(!config.notifications);
+}
+
+#[test]
+fn unknown_profile() {
+    let file: ConfigFile = toml::from_str(
+        r#"
+        [profiles.deep]
+        work_duration = 50
+
+        [profiles.admin]
+        work_duration = 15
+        "#,
+    )
+    .expect("Failed to parse config");
+
+    assert_eq!(
+        file.profile("deep").unwrap().work_duration,
+        Some(minutes(50))
+    );
+
+    let err = file.profile("focus").unwrap_err();
+    assert!(matches!(err, ConfigError::UnknownProfile(..)));
+    assert_eq!(
+        err.to_string(),
+        "Unknown profile 'focus', available profiles: admin, deep."
+    );
+}
+
+// the environment belongs to the whole process, so this is the only test here that changes it
+#[test]
+fn resolve_defaults_file_profile_env_and_cli() {
+    let path = temp_dir("config").join("config.toml");
+    fs::write(
+        &path,
+        r#"
+        work_duration = 50
+        break_duration = 10
+        long_break_interval = 3
+        auto = true
+
+        [profiles.short]
+        work_duration = 15
+        break_duration = 3
+        "#,
+    )
+    .expect("Failed to write config");
+
+    std::env::set_var("POMO_CONFIG", &path);
+    std::env::set_var("POMO_BREAK_DURATION", "4m");
+
+    let cli = Settings {
+        auto: Some(false),
+        ..Settings::default()
+    };
+    let config = config::resolve(Some("short"), cli.clone()).expect("Failed to resolve config");
+
+    // profile over file, environment over profile, command line over file
+    assert_eq!(config.work_duration, minutes(15));
+    assert_eq!(config.break_duration, minutes(4));
+    assert_eq!(config.long_break_interval, 3);
+    assert_eq!(config.long_break_duration, minutes(15));
+    assert!(!config.auto);
+
+    let cli = Settings {
+        break_duration: Some(minutes(6)),
+        ..cli
+    };
+    let config = config::resolve(None, cli.clone()).expect("Failed to resolve config");
+    assert_eq!(config.work_duration, minutes(50));
+    assert_eq!(config.break_duration, minutes(6));
+
+    assert!(matches!(
+        config::resolve(Some("long"), cli.clone()),
+        Err(ConfigError::UnknownProfile(..))
+    ));
+
+    std::env::set_var("POMO_AUTO", "maybe");
+    let err = config::resolve(None, cli).unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidEnv(..)));
+    assert_eq!(err.to_string(), "Invalid value 'maybe' for POMO_AUTO.");
+
+    std::env::remove_var("POMO_AUTO");
+    std::env::remove_var("POMO_BREAK_DURATION");
+    std::env::remove_var("POMO_CONFIG");
+}
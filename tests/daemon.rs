@@ -0,0 +1,158 @@
+// Drives a daemon over its socket like the pomo commands do. The timers run on tokio's paused
+// clock, while the daemon answers each connection on a thread of its own.
+mod common;
+
+use common::{minutes, start_command, TestDaemon};
+use pomo_cli::client::ClientError;
+use pomo_cli::daemon;
+use pomo_cli::protocol::{Command, ErrorCode, EventKind, Reply};
+use pomo_cli::timer::{TimerEvent, TimerState};
+use std::time::Duration;
+use tokio::time::sleep;
+
+fn error_code(result: Result<impl std::fmt::Debug, ClientError>) -> ErrorCode {
+    match result {
+        Err(ClientError::Daemon(error)) => error.code,
+        other => panic!("Expected an error from the daemon, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn commands_go_to_the_default_or_only_session() {
+    let daemon = TestDaemon::serve("default");
+
+    // the only session is used without a name
+    daemon.start("review");
+    daemon
+        .request(None, Command::Pause)
+        .expect("Failed to pause timer");
+    assert_eq!(daemon.status(None).unwrap().state, TimerState::Paused);
+
+    // with several, it's the one named default
+    daemon.start("default");
+    assert_eq!(daemon.status(None).unwrap().state, TimerState::Running);
+    assert_eq!(
+        daemon.status(Some("review")).unwrap().state,
+        TimerState::Paused
+    );
+
+    let Reply::Sessions { sessions } = daemon.request(None, Command::List).unwrap() else {
+        panic!("Expected the sessions");
+    };
+    let names = sessions.iter().map(|session| session.name.as_str());
+    assert_eq!(names.collect::<Vec<_>>(), ["default", "review"]);
+
+    assert_eq!(
+        error_code(daemon.status(Some("meeting"))),
+        ErrorCode::SessionNotFound
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_default_among_several_sessions() {
+    let daemon = TestDaemon::serve("several");
+    daemon.start("review");
+    daemon.start("meeting");
+
+    assert_eq!(error_code(daemon.status(None)), ErrorCode::SessionNotFound);
+
+    // a name can only be used once at a time
+    assert_eq!(
+        error_code(daemon.request(Some("review"), start_command())),
+        ErrorCode::SessionExists
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn stopping_the_last_session_shuts_down() {
+    let daemon = TestDaemon::serve("stop");
+    daemon.start("review");
+    daemon.start("meeting");
+
+    daemon
+        .request(Some("review"), Command::Stop)
+        .expect("Failed to stop timer");
+    assert_eq!(
+        error_code(daemon.status(Some("review"))),
+        ErrorCode::SessionNotFound
+    );
+    assert!(daemon.shutdown.is_empty());
+
+    daemon
+        .request(None, Command::Stop)
+        .expect("Failed to stop timer");
+    daemon
+        .shutdown
+        .recv_timeout(Duration::from_secs(5))
+        .expect("The daemon didn't shut down");
+
+    assert_eq!(error_code(daemon.status(None)), ErrorCode::SessionNotFound);
+}
+
+#[tokio::test(start_paused = true)]
+async fn subscription_streams_events() {
+    let daemon = TestDaemon::serve("subscribe");
+    daemon.start("review");
+    daemon.start("meeting");
+
+    let mut review = daemon
+        .client(Some("review"))
+        .subscribe(false, false)
+        .expect("Failed to subscribe");
+    let mut all = daemon
+        .client(None)
+        .subscribe(false, true)
+        .expect("Failed to subscribe");
+
+    let next = |subscription: &mut pomo_cli::client::Subscription| {
+        let event = subscription
+            .next()
+            .expect("The subscription ended")
+            .expect("Failed to read event");
+        (event.session, event.event)
+    };
+
+    // the current status comes first
+    assert_eq!(next(&mut review), ("review".to_string(), EventKind::Status));
+    assert_eq!(next(&mut all), ("meeting".to_string(), EventKind::Status));
+    assert_eq!(next(&mut all), ("review".to_string(), EventKind::Status));
+
+    daemon
+        .request(Some("meeting"), Command::Pause)
+        .expect("Failed to pause timer");
+    assert_eq!(
+        next(&mut all),
+        ("meeting".to_string(), EventKind::Timer(TimerEvent::Pause))
+    );
+
+    sleep(minutes(25) + Duration::from_secs(1)).await;
+    let finish = EventKind::Timer(TimerEvent::Finish);
+    assert_eq!(next(&mut review), ("review".to_string(), finish));
+    assert_eq!(next(&mut all), ("review".to_string(), finish));
+
+    // a subscription to a single session ends once it is stopped
+    daemon
+        .request(Some("review"), Command::Stop)
+        .expect("Failed to stop timer");
+    assert_eq!(
+        next(&mut review),
+        ("review".to_string(), EventKind::Timer(TimerEvent::Stop))
+    );
+    assert!(review.next().is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn session_names() {
+    for name in ["default", "review-2", "deep_work"] {
+        assert!(daemon::validate_session_name(name).is_ok());
+    }
+    for name in ["", "../etc", "two words", "naïve"] {
+        assert!(daemon::validate_session_name(name).is_err());
+    }
+
+    let daemon = TestDaemon::serve("names");
+    assert_eq!(
+        error_code(daemon.request(Some("../review"), start_command())),
+        ErrorCode::InvalidSessionName
+    );
+}
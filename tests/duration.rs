@@ -0,0 +1,107 @@
+use pomo_cli::config::{ConfigFile, Settings};
+use pomo_cli::controller::Config;
+use pomo_cli::duration;
+use pomo_cli::protocol::Request;
+use std::time::Duration;
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+#[test]
+fn parse_units() {
+    assert_eq!(duration::parse("90s"), Ok(secs(90)));
+    assert_eq!(duration::parse("25m"), Ok(secs(25 * 60)));
+    assert_eq!(duration::parse("1h"), Ok(secs(3600)));
+    assert_eq!(duration::parse("1h15m"), Ok(secs(75 * 60)));
+    assert_eq!(duration::parse("1h0m30s"), Ok(secs(3630)));
+}
+
+#[test]
+fn parse_clock() {
+    assert_eq!(duration::parse("1:30:00"), Ok(secs(90 * 60)));
+    assert_eq!(duration::parse("25:00"), Ok(secs(25 * 60)));
+    assert_eq!(duration::parse("0:45"), Ok(secs(45)));
+}
+
+#[test]
+fn bare_number_is_minutes() {
+    assert_eq!(duration::parse("25"), Ok(secs(25 * 60)));
+}
+
+#[test]
+fn zero_is_refused() {
+    for input in ["0", "0m", "0h0m0s", "0:00"] {
+        let err = duration::parse(input).expect_err(input);
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid duration '{}', it can't be zero.", input)
+        );
+    }
+
+    let err = toml::from_str::<ConfigFile>("work_duration = 0").expect_err("0");
+    assert!(err.to_string().contains("it can't be zero"));
+
+    let request = r#"{"version":2,"id":1,"command":"adjust","adjustment":"set","duration":0}"#;
+    assert!(Request::parse(request).is_err());
+
+    std::env::set_var("POMO_WORK_DURATION", "0s");
+    assert!(Settings::from_env().is_err());
+}
+
+#[test]
+fn invalid_durations() {
+    for input in [
+        "", "m", "5x", "1m1h", "5m5m", "1.5m", "-5m", "10:60", "1:2:3:4", "25m 30",
+    ] {
+        let err = duration::parse(input).expect_err(input);
+        assert!(err.to_string().starts_with("Invalid duration"));
+    }
+}
+
+#[test]
+fn config_file_durations() {
+    let file: ConfigFile = toml::from_str(
+        r#"
+        work_duration = "50m"
+        break_duration = 10
+
+        [alerts]
+        repeat_interval = 45
+        "#,
+    )
+    .expect("Failed to parse config");
+
+    let config = file.settings.apply(Config::default());
+    assert_eq!(config.work_duration, secs(50 * 60));
+    assert_eq!(config.break_duration, secs(10 * 60));
+    assert_eq!(file.alerts.repeat_interval, secs(45));
+
+    let err = toml::from_str::<ConfigFile>(r#"work_duration = "soon""#).expect_err("soon");
+    assert!(err.to_string().contains("Invalid duration 'soon'"));
+}
+
+#[test]
+fn socket_durations() {
+    let request =
+        r#"{"version":2,"id":1,"command":"adjust","adjustment":"add","duration":"1m30s"}"#;
+    let json = serde_json::to_value(Request::parse(request).expect("Failed to parse request"))
+        .expect("Failed to serialize request");
+    assert_eq!(json["duration"], 90);
+
+    let config: Config = serde_json::from_str(
+        r#"{"work_duration":1500,"break_duration":"5m","long_break_duration":900,
+            "long_break_interval":4,"auto":false}"#,
+    )
+    .expect("Failed to parse config");
+    assert_eq!(config.work_duration, secs(1500));
+    assert_eq!(config.break_duration, secs(300));
+    assert_eq!(config.long_break_duration, secs(900));
+
+    // the {secs, nanos} objects of serde's own format aren't read
+    let config = serde_json::from_str::<Config>(
+        r#"{"work_duration":{"secs":1500,"nanos":0},"break_duration":300,
+            "long_break_duration":900,"long_break_interval":4,"auto":false}"#,
+    );
+    assert!(config.is_err());
+}
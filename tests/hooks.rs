@@ -0,0 +1,264 @@
+mod common;
+
+use common::temp_dir;
+use pomo_cli::controller::{Config, Controller, NotStarted};
+use pomo_cli::hooks::{HookConfig, Hooks, Outcome, Payload};
+use pomo_cli::timer::{Timer, TimerState, TimerType};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn hooks_dir(name: &str) -> PathBuf {
+    temp_dir(&format!("hooks-{}", name))
+}
+
+// e.g. "pause.sh" or "finish.d/10-slack.sh"
+fn write_hook(dir: &Path, name: &str, script: &str) {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).expect("Failed to create hooks dir");
+    fs::write(&path, script).expect("Failed to write hook");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        .expect("Failed to make hook executable");
+}
+
+fn payload(event: &str) -> Payload {
+    let timer = Timer::new(TimerType::Work, &Duration::from_secs(25 * 60));
+    let mut timer = timer.lock().unwrap();
+    timer.set_label(Some("review".to_string()));
+    Payload::new(event, "default", &timer)
+}
+
+fn hooks(dir: &Path, timeout: Duration) -> Arc<Hooks> {
+    Hooks::new(
+        dir.to_path_buf(),
+        HookConfig {
+            timeout,
+            ..HookConfig::default()
+        },
+        dir.join("hooks.log"),
+    )
+}
+
+fn names(hooks: &Hooks, event: &str, timer_type: TimerType) -> Vec<String> {
+    hooks
+        .scripts(event, timer_type, None)
+        .iter()
+        .map(|path| hooks.name(path))
+        .collect()
+}
+
+// runs the only hook for the event
+fn run(hooks: &Hooks, payload: &Payload) -> Outcome {
+    let scripts = hooks.scripts(&payload.event, payload.timer_type, None);
+    assert_eq!(scripts.len(), 1);
+
+    hooks.run(&scripts[0], payload).expect("Failed to run hook")
+}
+
+#[test]
+fn hook_gets_arguments_env_and_payload() {
+    let dir = hooks_dir("payload");
+    write_hook(
+        &dir,
+        "pause.sh",
+        "#!/bin/sh\necho \"$1 $2 $POMO_SESSION $POMO_LABEL $POMO_DURATION\"\ncat\necho done >&2\n",
+    );
+
+    let outcome = run(&hooks(&dir, Duration::from_secs(10)), &payload("pause"));
+
+    assert!(outcome.success());
+    assert_eq!(outcome.stderr, "done\n");
+
+    let mut lines = outcome.stdout.lines();
+    assert_eq!(lines.next(), Some("pause work default review 1500"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(lines.next().expect("Missing payload")).expect("Invalid payload");
+    assert_eq!(json["event"], "pause");
+    assert_eq!(json["remaining_secs"], 1500);
+    assert_eq!(json["label"], "review");
+}
+
+#[test]
+fn resume_falls_back_to_start() {
+    let dir = hooks_dir("resume");
+    let hooks = hooks(&dir, Duration::from_secs(10));
+    assert!(hooks.scripts("resume", TimerType::Work, None).is_empty());
+
+    write_hook(&dir, "start.sh", "#!/bin/sh\necho $POMO_EVENT\n");
+
+    let outcome = run(&hooks, &payload("resume"));
+    assert_eq!(outcome.stdout, "resume\n");
+
+    assert!(hooks.scripts("stop", TimerType::Work, None).is_empty());
+}
+
+#[test]
+fn slow_hook_is_killed() {
+    let dir = hooks_dir("timeout");
+    write_hook(
+        &dir,
+        "finish.sh",
+        "#!/bin/sh\necho started\nsleep 30 &\nwait\n",
+    );
+
+    let outcome = run(&hooks(&dir, Duration::from_millis(300)), &payload("finish"));
+
+    assert!(!outcome.success());
+    assert!(outcome.status.is_none());
+    assert!(outcome.elapsed < Duration::from_secs(5));
+    assert_eq!(outcome.stdout, "started\n");
+}
+
+#[test]
+fn background_process_does_not_hold_up_the_hook() {
+    let dir = hooks_dir("background");
+    write_hook(
+        &dir,
+        "finish.sh",
+        "#!/bin/sh\necho started\nsleep 30 &\necho done >&2\n",
+    );
+
+    let started_at = std::time::Instant::now();
+    let outcome = run(&hooks(&dir, Duration::from_secs(10)), &payload("finish"));
+
+    assert!(outcome.success());
+    assert!(started_at.elapsed() < Duration::from_secs(5));
+    assert_eq!(outcome.stdout, "started\n");
+    assert_eq!(outcome.stderr, "done\n");
+}
+
+#[test]
+fn hook_writing_before_reading_stdin() {
+    let dir = hooks_dir("output");
+    write_hook(&dir, "finish.sh", "#!/bin/sh\nseq 100000\ncat >/dev/null\n");
+
+    let outcome = run(&hooks(&dir, Duration::from_secs(10)), &payload("finish"));
+
+    assert!(outcome.success());
+    assert_eq!(outcome.stdout.lines().count(), 100000);
+}
+
+#[test]
+fn directory_scripts_run_in_order() {
+    let dir = hooks_dir("directory");
+    write_hook(&dir, "start.d/20-dnd.sh", "#!/bin/sh\n");
+    write_hook(&dir, "start.d/10-slack", "#!/bin/sh\n");
+    write_hook(&dir, "start.sh", "#!/bin/sh\n");
+
+    // not executable, or hidden
+    fs::write(dir.join("start.d/README"), "notes").unwrap();
+    write_hook(&dir, "start.d/.20-dnd.sh.swp", "#!/bin/sh\n");
+
+    let hooks = hooks(&dir, Duration::from_secs(10));
+    assert_eq!(
+        names(&hooks, "start", TimerType::Work),
+        ["start.sh", "start.d/10-slack", "start.d/20-dnd.sh"]
+    );
+    assert_eq!(names(&hooks, "resume", TimerType::Work).len(), 3);
+}
+
+#[test]
+fn scripts_filtered_by_timer_type() {
+    let dir = hooks_dir("filter");
+    write_hook(&dir, "finish.d/10-slack.work.sh", "#!/bin/sh\n");
+    write_hook(&dir, "finish.d/20-music.break", "#!/bin/sh\n");
+    write_hook(&dir, "finish.d/30-log.sh", "#!/bin/sh\n");
+
+    let hooks = hooks(&dir, Duration::from_secs(10));
+    assert_eq!(
+        names(&hooks, "finish", TimerType::Work),
+        ["finish.d/10-slack.work.sh", "finish.d/30-log.sh"]
+    );
+    assert_eq!(
+        names(&hooks, "finish", TimerType::Break),
+        ["finish.d/20-music.break", "finish.d/30-log.sh"]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failing_script_does_not_stop_the_others() {
+    let dir = hooks_dir("failure");
+    write_hook(&dir, "pause.d/10-fail.sh", "#!/bin/sh\nexit 3\n");
+    write_hook(&dir, "pause.d/20-broken.sh", "#!/nonexistent/shell\n");
+    write_hook(
+        &dir,
+        "pause.d/30-ok.sh",
+        &format!("#!/bin/sh\ntouch {}/ran\n", dir.display()),
+    );
+
+    let hooks = hooks(&dir, Duration::from_secs(10));
+    let controller = Controller::new(Config::default());
+    hooks.watch("default", &controller);
+
+    Controller::start(&controller).expect("Failed to start timer");
+    Controller::pause(&controller);
+
+    // the last script's outcome is the last line of the log
+    let mut log = String::new();
+
+    for _ in 0..100 {
+        log = fs::read_to_string(dir.join("hooks.log")).unwrap_or_default();
+        if log.contains("30-ok.sh") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    assert!(dir.join("ran").exists());
+    assert!(log.contains("pause.d/10-fail.sh exited with exit status: 3"));
+    assert!(log.contains("Failed to run pause.d/20-broken.sh"));
+    assert!(log.contains("pause.d/30-ok.sh exited with exit status: 0"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn before_hook_changes_the_timer() {
+    let dir = hooks_dir("override");
+    write_hook(
+        &dir,
+        "before-start.d/10-duration.sh",
+        "#!/bin/sh\necho checking\necho duration=10m\n",
+    );
+    write_hook(
+        &dir,
+        "before-start.d/20-label.sh",
+        "#!/bin/sh\necho \"label=$POMO_DURATION\"\n",
+    );
+
+    let controller = Controller::new(Config::default());
+    hooks(&dir, Duration::from_secs(10)).guard("default", &controller);
+
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.total_secs, 600);
+    assert_eq!(status.label.as_deref(), Some("600"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn before_hook_cancels_the_break() {
+    let dir = hooks_dir("veto");
+    write_hook(
+        &dir,
+        "before-break.sh",
+        "#!/bin/sh\necho \"deploy still running\" >&2\nexit 1\n",
+    );
+
+    let controller = Controller::new(Config::default());
+    hooks(&dir, Duration::from_secs(10)).guard("default", &controller);
+
+    // work timers aren't checked by the before-break hooks
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let reason = Controller::next(&controller).expect_err("Started a break");
+    assert_eq!(
+        reason,
+        NotStarted::Cancelled("deploy still running".to_string())
+    );
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Work);
+    assert_eq!(status.state, TimerState::Running);
+}
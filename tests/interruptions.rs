@@ -0,0 +1,67 @@
+use chrono::Local;
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::history::{Entry, Stats};
+use pomo_cli::timer::{Interruption, InterruptionKind, TimerEvent, TimerState};
+
+fn interruption(kind: InterruptionKind, note: Option<&str>) -> Interruption {
+    Interruption {
+        kind,
+        note: note.map(String::from),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn count_interruptions_of_work_timer() {
+    let controller = Controller::new(Config::default());
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let internal = interruption(InterruptionKind::Internal, None);
+    let external = interruption(InterruptionKind::External, Some("phone call"));
+
+    assert!(Controller::interrupt(&controller, internal, false));
+    assert!(Controller::interrupt(&controller, external.clone(), true));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Paused);
+    assert_eq!(
+        (status.interruptions.internal, status.interruptions.external),
+        (1, 1)
+    );
+
+    let timer = Controller::get_current_timer(&controller);
+    let entry = Entry::new("default", TimerEvent::Interrupt, &timer.lock().unwrap());
+    assert_eq!(entry.interruption, Some(external));
+    assert_eq!(entry.interruptions.total(), 2);
+
+    // the break has its own, empty, count and can't be interrupted
+    Controller::next(&controller).expect("Failed to start next timer");
+    let break_interruption = interruption(InterruptionKind::Internal, None);
+    assert!(!Controller::interrupt(
+        &controller,
+        break_interruption,
+        false
+    ));
+    assert!(Controller::status(&controller).interruptions.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn interruptions_in_stats() {
+    let controller = Controller::new(Config::default());
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let timer = Controller::get_current_timer(&controller);
+    let mut entries = Vec::new();
+
+    for kind in [InterruptionKind::Internal, InterruptionKind::External] {
+        Controller::interrupt(&controller, interruption(kind, None), false);
+        entries.push(Entry::new(
+            "default",
+            TimerEvent::Interrupt,
+            &timer.lock().unwrap(),
+        ));
+    }
+
+    let stats = Stats::compute(&entries, Local::now().date_naive());
+    assert_eq!(stats.today.interruptions, 2);
+    assert_eq!(stats.today.pomodoros, 0);
+}
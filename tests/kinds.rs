@@ -0,0 +1,204 @@
+mod common;
+
+use chrono::Local;
+use common::temp_dir;
+use pomo_cli::config::ConfigFile;
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::history::{Entry, Stats};
+use pomo_cli::hooks::{HookConfig, Hooks};
+use pomo_cli::status::Template;
+use pomo_cli::timer::{Timer, TimerEvent, TimerSnapshot, TimerState, TimerType};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn with_kinds(sequence: &str) -> Arc<Mutex<Controller>> {
+    let file: ConfigFile = toml::from_str(&format!(
+        r#"
+        sequence = "{}"
+
+        [kinds.meeting]
+        prefix = "M"
+
+        [kinds.walk]
+        type = "break"
+        "#,
+        sequence
+    ))
+    .expect("Failed to parse config");
+
+    let config = file.settings.apply(Config::default());
+    assert_eq!(config.unknown_kind(), None);
+
+    Controller::new(config)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn long_break_is_its_own_type() {
+    // next skips the work timers, let them count towards the long break
+    let controller = Controller::new(Config {
+        count_skipped: true,
+        ..Config::default()
+    });
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let mut types = vec![Controller::status(&controller).timer_type];
+    for _ in 0..7 {
+        Controller::next(&controller).expect("Failed to start next timer");
+        types.push(Controller::status(&controller).timer_type);
+    }
+
+    assert_eq!(types[5], TimerType::Break);
+    assert_eq!(types[7], TimerType::LongBreak);
+    assert!(types[7].is_break());
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.title(), "Long break");
+    assert_eq!(
+        Template::parse("{prefix} {type}").unwrap().render(&status),
+        "L long_break"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn kinds_in_sequence() {
+    let controller = with_kinds("meeting 30m, walk 10m, work 25m");
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Work);
+    assert_eq!(status.kind.as_deref(), Some("meeting"));
+    assert_eq!(status.title(), "Meeting");
+    assert_eq!(
+        Template::parse("{prefix} {kind}").unwrap().render(&status),
+        "M meeting"
+    );
+
+    // a kind without a prefix has the one of its timer type
+    Controller::next(&controller).expect("Failed to start next timer");
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Break);
+    assert_eq!(
+        Template::parse("{prefix} {kind}").unwrap().render(&status),
+        "B walk"
+    );
+
+    Controller::next(&controller).expect("Failed to start next timer");
+    let status = Controller::status(&controller);
+    assert_eq!(status.kind, None);
+    assert_eq!(
+        Template::parse("{prefix} {kind}").unwrap().render(&status),
+        "W work"
+    );
+}
+
+#[test]
+fn hooks_filtered_by_kind() {
+    let dir = temp_dir("kinds");
+    fs::create_dir_all(dir.join("start.d")).expect("Failed to create hooks dir");
+
+    for name in [
+        "10-standup.meeting.sh",
+        "20-focus.work.sh",
+        "30-music.break",
+        "40-stretch.long_break",
+        "50-log.sh",
+    ] {
+        let path = dir.join("start.d").join(name);
+        fs::write(&path, "#!/bin/sh\n").expect("Failed to write hook");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .expect("Failed to make hook executable");
+    }
+
+    let hooks = Hooks::new(
+        dir.clone(),
+        HookConfig {
+            kinds: vec!["meeting".to_string()],
+            ..HookConfig::default()
+        },
+        dir.join("hooks.log"),
+    );
+
+    let names = |timer_type, kind| -> Vec<String> {
+        hooks
+            .scripts("start", timer_type, kind)
+            .iter()
+            .map(|path| hooks.name(path))
+            .collect()
+    };
+
+    assert_eq!(
+        names(TimerType::Work, None),
+        ["start.d/20-focus.work.sh", "start.d/50-log.sh"]
+    );
+    assert_eq!(
+        names(TimerType::Work, Some("meeting")),
+        [
+            "start.d/10-standup.meeting.sh",
+            "start.d/20-focus.work.sh",
+            "start.d/50-log.sh"
+        ]
+    );
+    assert_eq!(
+        names(TimerType::Break, None),
+        ["start.d/30-music.break", "start.d/50-log.sh"]
+    );
+    assert_eq!(
+        names(TimerType::LongBreak, None),
+        [
+            "start.d/30-music.break",
+            "start.d/40-stretch.long_break",
+            "start.d/50-log.sh"
+        ]
+    );
+}
+
+#[test]
+fn kinds_have_their_own_stats() {
+    let timer = |kind: Option<&str>| {
+        let timer = Timer::restore(&TimerSnapshot {
+            timer_type: TimerType::Work,
+            state: TimerState::Stopped,
+            duration: Duration::from_secs(30 * 60),
+            remaining: Duration::ZERO,
+            kind: kind.map(String::from),
+            label: None,
+            task: None,
+            interruptions: Vec::new(),
+        });
+        let timer = timer.lock().unwrap();
+        Entry::new("default", TimerEvent::Finish, &timer)
+    };
+
+    let entries = [timer(None), timer(Some("meeting")), timer(Some("meeting"))];
+    let stats = Stats::compute(&entries, Local::now().date_naive());
+
+    assert_eq!(stats.today.pomodoros, 1);
+    assert_eq!(stats.completed, 1);
+    for totals in [
+        stats.kinds["meeting"].today,
+        stats.kinds["meeting"].week,
+        stats.kinds["meeting"].month,
+    ] {
+        assert_eq!(totals.pomodoros, 2);
+        assert_eq!(totals.focus_secs, 60 * 60);
+    }
+}
+
+#[test]
+fn long_break_is_restored() {
+    let snapshot: TimerSnapshot = serde_json::from_str(
+        r#"{"timer_type":"long_break","state":"paused","duration":{"secs":900,"nanos":0},
+            "remaining":{"secs":600,"nanos":0}}"#,
+    )
+    .expect("Failed to parse snapshot");
+
+    let timer = Timer::restore(&snapshot);
+    let timer = timer.lock().unwrap();
+    assert_eq!(timer.timer_type(), TimerType::LongBreak);
+    assert!(timer.is_long_break());
+
+    let saved = serde_json::to_value(timer.snapshot()).unwrap();
+    assert_eq!(saved["timer_type"], "long_break");
+}
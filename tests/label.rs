@@ -0,0 +1,48 @@
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::history::Entry;
+use pomo_cli::timer::{TimerEvent, TimerType};
+use std::sync::{Arc, Mutex};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn label_stays_with_next_timers() {
+    let controller = Controller::new(Config::default());
+    Controller::label(&controller, Some("migration".to_string()), Some(3));
+    Controller::start(&controller).expect("Failed to start timer");
+
+    Controller::next(&controller).expect("Failed to start next timer");
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Break);
+    assert_eq!(status.label.as_deref(), Some("migration"));
+    assert_eq!(task(&controller), Some(3));
+
+    // a label of its own isn't the task anymore
+    Controller::label(&controller, Some("lunch".to_string()), None);
+    assert_eq!(task(&controller), None);
+
+    Controller::label(&controller, None, None);
+    Controller::next(&controller).expect("Failed to start next timer");
+    assert_eq!(Controller::status(&controller).label, None);
+}
+
+fn task(controller: &Arc<Mutex<Controller>>) -> Option<u64> {
+    let timer = Controller::get_current_timer(controller);
+    let task = timer.lock().unwrap().task();
+    task
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn label_in_history() {
+    let controller = Controller::new(Config::default());
+    Controller::label(&controller, Some("PR review #123".to_string()), None);
+
+    let timer = Controller::get_current_timer(&controller);
+    let entry = Entry::new("default", TimerEvent::Start, &timer.lock().unwrap());
+    assert_eq!(entry.label.as_deref(), Some("PR review #123"));
+
+    // older entries have no label
+    let line = serde_json::to_string(&entry).expect("Failed to serialize entry");
+    let line = line.replace(r#","label":"PR review #123""#, "");
+    let entry: Entry = serde_json::from_str(&line).expect("Failed to parse entry");
+    assert_eq!(entry.label, None);
+}
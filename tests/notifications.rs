@@ -0,0 +1,210 @@
+#![cfg(feature = "notifications")]
+
+mod common;
+
+use common::config;
+use pomo_cli::controller::Controller;
+use pomo_cli::notifications::Notifier;
+use pomo_cli::timer::TimerType;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Stdio};
+use std::time::Duration;
+use zbus::names::BusName;
+use zbus::zvariant::OwnedValue;
+use zbus::Connection;
+
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+// a private session bus, so the tests don't show notifications on the desktop
+struct Bus {
+    daemon: Child,
+    address: String,
+}
+
+impl Bus {
+    fn start() -> Option<Bus> {
+        let mut daemon = std::process::Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.as_mut()?)
+            .read_line(&mut address)
+            .ok()?;
+
+        Some(Bus {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+
+    async fn connect(&self) -> Connection {
+        zbus::connection::Builder::address(self.address.as_str())
+            .expect("Invalid bus address")
+            .build()
+            .await
+            .expect("Failed to connect to bus")
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+#[derive(Debug)]
+struct Notification {
+    id: u32,
+    summary: String,
+    actions: Vec<String>,
+}
+
+// records notifications instead of showing them
+struct Server {
+    notifications: flume::Sender<Notification>,
+    next_id: u32,
+}
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl Server {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &mut self,
+        _app_name: &str,
+        _replaces_id: u32,
+        _app_icon: &str,
+        summary: &str,
+        _body: &str,
+        actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        self.next_id += 1;
+
+        let _ = self.notifications.send(Notification {
+            id: self.next_id,
+            summary: summary.to_string(),
+            actions,
+        });
+
+        self.next_id
+    }
+}
+
+async fn serve(bus: &Bus) -> (Connection, flume::Receiver<Notification>) {
+    let (tx, rx) = flume::unbounded();
+
+    let server = Server {
+        notifications: tx,
+        next_id: 0,
+    };
+
+    let connection = zbus::connection::Builder::address(bus.address.as_str())
+        .expect("Invalid bus address")
+        .name(INTERFACE)
+        .expect("Invalid name")
+        .serve_at(PATH, server)
+        .expect("Failed to serve notifications")
+        .build()
+        .await
+        .expect("Failed to start notification server");
+
+    (connection, rx)
+}
+
+async fn next_notification(notifications: &flume::Receiver<Notification>) -> Notification {
+    tokio::time::timeout(Duration::from_secs(5), notifications.recv_async())
+        .await
+        .expect("No notification was shown")
+        .expect("Notification server stopped")
+}
+
+async fn click(server: &Connection, notification: &Notification, action: &str) {
+    assert!(notification.actions.iter().any(|key| key == action));
+
+    // the server records the notification before its reply reaches the notifier
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    server
+        .emit_signal(
+            None::<BusName>,
+            PATH,
+            INTERFACE,
+            "ActionInvoked",
+            &(notification.id, action),
+        )
+        .await
+        .expect("Failed to emit signal");
+}
+
+// waits for the controller to get into the expected state
+async fn eventually(check: impl Fn() -> bool) {
+    for _ in 0..50 {
+        if check() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("The controller didn't change in time");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn extend_from_start_notification() {
+    let Some(bus) = Bus::start() else {
+        println!("dbus-daemon is not available, skipping");
+        return;
+    };
+
+    let (server, notifications) = serve(&bus).await;
+    let notifier = Notifier::with_connection(bus.connect().await)
+        .await
+        .expect("Failed to connect notifier");
+
+    let controller = Controller::new(config(Duration::from_secs(60)));
+    notifier.watch("default", &controller);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let notification = next_notification(&notifications).await;
+    assert_eq!(notification.summary, "Work timer started");
+
+    click(&server, &notification, "extend").await;
+
+    eventually(|| Controller::status(&controller).total_secs == 6 * 60).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn start_break_from_finish_notification() {
+    let Some(bus) = Bus::start() else {
+        println!("dbus-daemon is not available, skipping");
+        return;
+    };
+
+    let (server, notifications) = serve(&bus).await;
+    let notifier = Notifier::with_connection(bus.connect().await)
+        .await
+        .expect("Failed to connect notifier");
+
+    let controller = Controller::new(config(Duration::from_millis(200)));
+    notifier.watch("review", &controller);
+    Controller::start(&controller).expect("Failed to start timer");
+
+    next_notification(&notifications).await;
+
+    let notification = next_notification(&notifications).await;
+    assert_eq!(notification.summary, "Work timer finished (review)");
+
+    click(&server, &notification, "next").await;
+
+    eventually(|| Controller::status(&controller).timer_type == TimerType::Break).await;
+
+    // the break started and shows its own notification, the old buttons no longer apply
+    let notification = next_notification(&notifications).await;
+    assert_eq!(notification.summary, "Break timer started (review)");
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::temp_dir;
+use pomo_cli::paths;
+use std::os::unix::fs::{symlink, PermissionsExt};
+
+fn mode(path: &std::path::Path) -> u32 {
+    std::fs::metadata(path)
+        .expect("Failed to read metadata")
+        .permissions()
+        .mode()
+        & 0o777
+}
+
+#[test]
+fn private_dir_is_created() {
+    let dir = temp_dir("paths-create").join("runtime").join("pomo");
+
+    paths::ensure_private_dir(&dir).expect("Failed to create directory");
+    assert_eq!(mode(&dir), 0o700);
+}
+
+#[test]
+fn private_dir_is_restricted() {
+    let dir = temp_dir("paths-restrict");
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755))
+        .expect("Failed to set permissions");
+
+    paths::ensure_private_dir(&dir).expect("Failed to restrict directory");
+    assert_eq!(mode(&dir), 0o700);
+}
+
+#[test]
+fn symlink_is_refused() {
+    let root = temp_dir("paths-symlink");
+    let target = root.join("target");
+    std::fs::create_dir(&target).expect("Failed to create directory");
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755))
+        .expect("Failed to set permissions");
+
+    let link = root.join("pomo");
+    symlink(&target, &link).expect("Failed to create symlink");
+
+    assert!(paths::ensure_private_dir(&link).is_err());
+
+    // the directory behind it is left alone
+    assert_eq!(mode(&target), 0o755);
+}
+
+#[test]
+fn file_is_refused() {
+    let file = temp_dir("paths-file").join("pomo");
+    std::fs::write(&file, "").expect("Failed to write file");
+
+    assert!(paths::ensure_private_dir(&file).is_err());
+}
+
+#[test]
+fn socket_is_private_from_the_start() {
+    let socket = temp_dir("paths-socket").join("pomo.sock");
+
+    let _listener = paths::bind_private(&socket).expect("Failed to bind socket");
+    assert_eq!(mode(&socket), 0o600);
+}
@@ -0,0 +1,25 @@
+mod common;
+
+use common::temp_dir;
+use pomo_cli::peer;
+use std::os::unix::net::{UnixListener, UnixStream};
+
+#[test]
+fn peer_of_a_socket_pair() {
+    let (ours, theirs) = UnixStream::pair().expect("Failed to create socket pair");
+
+    assert_eq!(peer::peer_uid(&ours).unwrap(), peer::current_uid());
+    assert_eq!(peer::peer_uid(&theirs).unwrap(), peer::current_uid());
+}
+
+// the daemon checks the connections it accepts
+#[test]
+fn peer_of_an_accepted_connection() {
+    let socket = temp_dir("peer").join("pomo.sock");
+    let listener = UnixListener::bind(&socket).expect("Failed to bind socket");
+
+    let _client = UnixStream::connect(&socket).expect("Failed to connect");
+    let (stream, _) = listener.accept().expect("Failed to accept connection");
+
+    assert_eq!(peer::peer_uid(&stream).unwrap(), peer::current_uid());
+}
@@ -0,0 +1,138 @@
+use pomo_cli::controller::Config;
+use pomo_cli::protocol::{
+    Command, ErrorCode, Outcome, Request, Response, COMMANDS, PROTOCOL_VERSION,
+};
+use pomo_cli::timer::{Adjustment, InterruptionKind};
+use std::time::Duration;
+
+fn error(response: Response) -> (Option<u64>, ErrorCode) {
+    match response.outcome {
+        Outcome::Error(error) => (response.id, error.code),
+        Outcome::Result(reply) => panic!("Expected an error, got {:?}", reply),
+    }
+}
+
+#[test]
+fn request_round_trip() {
+    let request = Request::new(
+        7,
+        Some("meeting".to_string()),
+        Command::Adjust {
+            adjustment: Adjustment::Add,
+            duration: Duration::from_secs(300),
+        },
+    );
+
+    let line = serde_json::to_string(&request).expect("Failed to write request");
+    let parsed = Request::parse(&line).expect("Failed to parse request");
+
+    assert_eq!(parsed.version, PROTOCOL_VERSION);
+    assert_eq!(parsed.id, 7);
+    assert_eq!(parsed.session.as_deref(), Some("meeting"));
+    assert!(matches!(
+        parsed.command,
+        Command::Adjust {
+            adjustment: Adjustment::Add,
+            duration,
+        } if duration == Duration::from_secs(300)
+    ));
+}
+
+#[test]
+fn other_version_is_refused() {
+    let line = format!(
+        r#"{{"version":{},"id":3,"command":"some_future_command"}}"#,
+        PROTOCOL_VERSION + 1
+    );
+    assert_eq!(
+        error(Request::parse(&line).unwrap_err()),
+        (Some(3), ErrorCode::VersionMismatch)
+    );
+
+    assert_eq!(
+        error(Request::parse(r#"{"id":3,"command":"status"}"#).unwrap_err()),
+        (Some(3), ErrorCode::InvalidRequest)
+    );
+}
+
+#[test]
+fn unknown_command() {
+    let line = format!(
+        r#"{{"version":{},"id":4,"command":"rewind"}}"#,
+        PROTOCOL_VERSION
+    );
+    assert_eq!(
+        error(Request::parse(&line).unwrap_err()),
+        (Some(4), ErrorCode::UnknownCommand)
+    );
+
+    // even without an id
+    let line = format!(r#"{{"version":{},"command":"rewind"}}"#, PROTOCOL_VERSION);
+    assert_eq!(
+        error(Request::parse(&line).unwrap_err()),
+        (None, ErrorCode::UnknownCommand)
+    );
+
+    // a known command with bad arguments is an invalid request
+    let line = format!(
+        r#"{{"version":{},"id":5,"command":"adjust","adjustment":"rewind"}}"#,
+        PROTOCOL_VERSION
+    );
+    assert_eq!(
+        error(Request::parse(&line).unwrap_err()),
+        (Some(5), ErrorCode::InvalidRequest)
+    );
+}
+
+#[test]
+fn malformed_json() {
+    for line in ["status", r#"{"version":2,"id":1,"#, ""] {
+        assert_eq!(
+            error(Request::parse(line).unwrap_err()),
+            (None, ErrorCode::InvalidRequest)
+        );
+    }
+}
+
+#[test]
+fn every_command_is_known() {
+    let commands = [
+        Command::Start {
+            config: Config::default(),
+            snapshot: None,
+            replace: false,
+            label: None,
+            task: None,
+        },
+        Command::List,
+        Command::Label {
+            label: None,
+            task: None,
+        },
+        Command::Pause,
+        Command::Resume,
+        Command::Stop,
+        Command::Next,
+        Command::Adjust {
+            adjustment: Adjustment::Set,
+            duration: Duration::from_secs(60),
+        },
+        Command::Interrupt {
+            kind: InterruptionKind::Internal,
+            note: None,
+            pause: false,
+        },
+        Command::Status,
+        Command::Subscribe {
+            ticks: false,
+            all: false,
+        },
+    ];
+
+    // one of each, in the order of the list
+    let names = commands.map(|command| {
+        let request = serde_json::to_value(Request::new(1, None, command)).unwrap();
+        request["command"].as_str().unwrap().to_string()
+    });
+    assert_eq!(names, COMMANDS);
+}
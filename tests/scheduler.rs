@@ -0,0 +1,104 @@
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::scheduler::{Ending, Scheduler, WorkCounts};
+use pomo_cli::timer::TimerType;
+
+// the breaks after work timers that end in these ways
+fn breaks(scheduler: &mut Scheduler, endings: &[Ending]) -> Vec<TimerType> {
+    endings
+        .iter()
+        .map(|&ending| {
+            let next = scheduler.break_after(ending);
+            scheduler.work_ended(ending);
+
+            if next == TimerType::LongBreak {
+                scheduler.long_break_started();
+            }
+
+            next
+        })
+        .collect()
+}
+
+const B: TimerType = TimerType::Break;
+const L: TimerType = TimerType::LongBreak;
+
+#[test]
+fn long_break_every_nth_work_timer() {
+    let mut scheduler = Scheduler::new(4, false);
+    assert_eq!(
+        breaks(&mut scheduler, &[Ending::Finished; 9]),
+        [B, B, B, L, B, B, B, L, B]
+    );
+    assert_eq!(
+        scheduler.counts(),
+        WorkCounts {
+            completed: 9,
+            skipped: 0,
+            since_long_break: 1
+        }
+    );
+}
+
+#[test]
+fn interval_of_zero_or_one() {
+    let mut never = Scheduler::new(0, false);
+    assert_eq!(breaks(&mut never, &[Ending::Finished; 5]), [B; 5]);
+    assert_eq!(never.position(TimerType::Work), None);
+
+    let mut always = Scheduler::new(1, false);
+    assert_eq!(breaks(&mut always, &[Ending::Finished; 3]), [L; 3]);
+}
+
+#[test]
+fn skipped_work_counts_only_when_asked() {
+    let endings = [
+        Ending::Finished,
+        Ending::Skipped,
+        Ending::Finished,
+        Ending::Skipped,
+        Ending::Finished,
+    ];
+
+    let mut scheduler = Scheduler::new(2, false);
+    assert_eq!(breaks(&mut scheduler, &endings), [B, B, L, B, B]);
+    assert_eq!(scheduler.counts().completed, 3);
+    assert_eq!(scheduler.counts().skipped, 2);
+
+    let mut scheduler = Scheduler::new(2, true);
+    assert_eq!(breaks(&mut scheduler, &endings), [B, L, B, L, B]);
+}
+
+#[test]
+fn position_in_the_cycle() {
+    let mut scheduler = Scheduler::new(3, false);
+    assert_eq!(scheduler.position(TimerType::Work), Some(1));
+
+    breaks(&mut scheduler, &[Ending::Finished; 2]);
+    assert_eq!(scheduler.position(TimerType::Break), Some(2));
+    assert_eq!(scheduler.position(TimerType::Work), Some(3));
+
+    breaks(&mut scheduler, &[Ending::Finished]);
+    assert_eq!(scheduler.position(TimerType::LongBreak), Some(3));
+    assert_eq!(scheduler.position(TimerType::Work), Some(1));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn skipping_work_delays_the_long_break() {
+    let controller = Controller::new(Config {
+        long_break_interval: 1,
+        ..Config::default()
+    });
+    Controller::start(&controller).expect("Failed to start timer");
+
+    // the status assumes the work timer will finish
+    assert_eq!(
+        Controller::status(&controller).next_timer_type,
+        TimerType::LongBreak
+    );
+
+    Controller::next(&controller).expect("Failed to start next timer");
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.timer_type, TimerType::Break);
+    assert_eq!((status.completed, status.skipped), (0, 1));
+}
@@ -0,0 +1,145 @@
+use pomo_cli::config::ConfigFile;
+use pomo_cli::controller::{Config, Controller, NotStarted};
+use pomo_cli::sequence::Sequence;
+use pomo_cli::status::SequenceStatus;
+use pomo_cli::timer::TimerType;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn sequence(input: &str) -> Sequence {
+    input.parse().expect("Failed to parse sequence")
+}
+
+fn with_sequence(input: &str) -> Arc<Mutex<Controller>> {
+    Controller::new(Config {
+        sequence: Some(sequence(input)),
+        ..Config::default()
+    })
+}
+
+// type and duration in minutes of the current timer
+fn current(controller: &Arc<Mutex<Controller>>) -> (TimerType, u64) {
+    let timer = Controller::get_current_timer(controller);
+    let timer = timer.lock().unwrap();
+    (timer.timer_type(), timer.duration().as_secs() / 60)
+}
+
+#[test]
+fn parse_and_display() {
+    let parsed = sequence("work 50m,break 10 ,  work 1h30m, long 30m");
+    assert_eq!(parsed.steps().len(), 4);
+    assert_eq!(parsed.steps()[1].duration, Duration::from_secs(600));
+    assert_eq!(parsed.steps()[3].kind, "long");
+    assert!(parsed.repeats());
+    assert_eq!(
+        parsed.to_string(),
+        "work 50m, break 10m, work 1h30m, long 30m"
+    );
+
+    let once = sequence("work 90m, break 20m, stop");
+    assert!(!once.repeats());
+    assert_eq!(once.to_string().parse::<Sequence>(), Ok(once));
+}
+
+#[test]
+fn invalid_sequences() {
+    for input in [
+        "",
+        "stop",
+        "work",
+        "work 0m",
+        "nap! 20m",
+        "repeat 20m",
+        "work 5x",
+        "work 50m,, break 5m",
+    ] {
+        let err = input.parse::<Sequence>().expect_err(input);
+        assert!(err.to_string().starts_with("Invalid sequence step"));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn controller_walks_and_repeats_the_sequence() {
+    let controller = with_sequence("work 52m, break 17m, work 90m, long 30m");
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let mut seen = vec![current(&controller)];
+    for _ in 0..4 {
+        Controller::next(&controller).expect("Failed to start next timer");
+        seen.push(current(&controller));
+    }
+
+    assert_eq!(
+        seen,
+        [
+            (TimerType::Work, 52),
+            (TimerType::Break, 17),
+            (TimerType::Work, 90),
+            (TimerType::LongBreak, 30),
+            (TimerType::Work, 52),
+        ]
+    );
+
+    let status = Controller::status(&controller);
+    assert_eq!(
+        status.sequence,
+        Some(SequenceStatus {
+            step: 1,
+            steps: 4,
+            repeat: true
+        })
+    );
+    assert_eq!(status.next_duration_secs, 17 * 60);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sequence_stops_at_its_end() {
+    let controller = with_sequence("work 50m, break 10m, stop");
+    Controller::start(&controller).expect("Failed to start timer");
+    Controller::next(&controller).expect("Failed to start next timer");
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.sequence.map(|sequence| sequence.step), Some(2));
+    assert_eq!(status.next_duration_secs, 0);
+
+    assert_eq!(
+        Controller::next(&controller),
+        Err(NotStarted::SequenceComplete)
+    );
+    assert_eq!(current(&controller), (TimerType::Break, 10));
+}
+
+#[test]
+fn sequence_in_config_file() {
+    let file: ConfigFile = toml::from_str(
+        r#"
+        [profiles.ultradian]
+        sequence = "work 90m, break 20m"
+        "#,
+    )
+    .expect("Failed to parse config");
+
+    let config = file.profiles["ultradian"].apply(Config::default());
+    assert_eq!(config.sequence, Some(sequence("work 90m, break 20m")));
+
+    // whether a kind exists is only known once the kinds of the config are in
+    let file: ConfigFile =
+        toml::from_str(r#"sequence = "work 50m, nap 5m""#).expect("Failed to parse config");
+    let config = file.settings.apply(Config::default());
+    assert_eq!(config.unknown_kind(), Some("nap"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn every_work_timer_starts_a_cycle() {
+    let controller = with_sequence("work 50m, work 50m, long 30m, break 5m");
+    Controller::start(&controller).expect("Failed to start timer");
+
+    let mut cycles = vec![Controller::status(&controller).cycle];
+    for _ in 0..4 {
+        Controller::next(&controller).expect("Failed to start next timer");
+        cycles.push(Controller::status(&controller).cycle);
+    }
+
+    // the long break and the break after it belong to the second work timer
+    assert_eq!(cycles, [1, 2, 2, 2, 3]);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use chrono::{Duration as ChronoDuration, TimeZone, Utc};
+use common::{minutes, temp_dir};
+use pomo_cli::controller::{Config, Controller, Snapshot};
+use pomo_cli::state::{self, DowntimePolicy, SavedState};
+use pomo_cli::timer::TimerState;
+use std::time::Duration;
+
+// a work timer with ten minutes left, saved at noon
+fn saved(state: TimerState) -> SavedState {
+    let controller = Controller::new(Config::default());
+    let mut snapshot: Snapshot = controller.lock().unwrap().snapshot();
+    snapshot.timer.state = state;
+    snapshot.timer.remaining = minutes(10);
+
+    SavedState {
+        saved_at: Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap(),
+        controller: snapshot,
+    }
+}
+
+fn remaining_after(state: TimerState, policy: DowntimePolicy, downtime_minutes: i64) -> Duration {
+    let saved = saved(state);
+    let now = saved.saved_at + ChronoDuration::minutes(downtime_minutes);
+
+    saved.restore(policy, now).timer.remaining
+}
+
+#[test]
+fn downtime_counts_towards_a_running_timer() {
+    assert_eq!(
+        remaining_after(TimerState::Running, DowntimePolicy::Count, 4),
+        minutes(6)
+    );
+
+    // a timer that would have finished meanwhile has no time left
+    assert_eq!(
+        remaining_after(TimerState::Running, DowntimePolicy::Count, 60),
+        Duration::ZERO
+    );
+
+    // nor does a clock that went backwards give it more
+    assert_eq!(
+        remaining_after(TimerState::Running, DowntimePolicy::Count, -5),
+        minutes(10)
+    );
+}
+
+#[test]
+fn downtime_is_discarded() {
+    assert_eq!(
+        remaining_after(TimerState::Running, DowntimePolicy::Discard, 4),
+        minutes(10)
+    );
+}
+
+#[test]
+fn paused_timer_keeps_its_time() {
+    for policy in [DowntimePolicy::Count, DowntimePolicy::Discard] {
+        assert_eq!(remaining_after(TimerState::Paused, policy, 4), minutes(10));
+    }
+
+    assert!("forget".parse::<DowntimePolicy>().is_err());
+}
+
+#[test]
+fn save_and_load() {
+    let path = temp_dir("state").join("sessions").join("default.json");
+
+    assert!(state::load(&path).expect("Failed to load state").is_none());
+
+    state::save(&path, &saved(TimerState::Running)).expect("Failed to save state");
+    let loaded = state::load(&path)
+        .expect("Failed to load state")
+        .expect("Missing state");
+    assert_eq!(loaded.controller.timer.remaining, minutes(10));
+    assert_eq!(loaded.controller.timer.state, TimerState::Running);
+
+    state::remove(&path);
+    assert!(state::load(&path).expect("Failed to load state").is_none());
+}
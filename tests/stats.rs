@@ -0,0 +1,128 @@
+mod common;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use common::record;
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::history::{Entry, Stats};
+use pomo_cli::timer::{Interruptions, TimerEvent, TimerType};
+use tokio::time::{sleep, Duration};
+
+// a Wednesday, its week started on the 12th
+fn today() -> NaiveDate {
+    date(10, 14)
+}
+
+fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, month, day).unwrap()
+}
+
+fn entry(date: NaiveDate, event: TimerEvent, timer_type: TimerType, minutes: u64) -> Entry {
+    let time = date.and_hms_opt(12, 0, 0).unwrap();
+
+    Entry {
+        time: Local.from_local_datetime(&time).unwrap(),
+        session: "default".to_string(),
+        event,
+        timer_type,
+        kind: None,
+        duration_secs: 25 * 60,
+        elapsed_secs: minutes * 60,
+        label: None,
+        interruptions: Interruptions::default(),
+        interruption: None,
+        suspended_secs: None,
+    }
+}
+
+fn finished(date: NaiveDate) -> Entry {
+    entry(date, TimerEvent::Finish, TimerType::Work, 25)
+}
+
+#[test]
+fn totals_of_today_this_week_and_this_month() {
+    let entries = [
+        // last month, in an earlier week
+        finished(date(9, 30)),
+        // this month, in an earlier week
+        finished(date(10, 2)),
+        // this week
+        finished(date(10, 12)),
+        entry(date(10, 12), TimerEvent::Finish, TimerType::Break, 5),
+        // today
+        finished(today()),
+        entry(today(), TimerEvent::Skip, TimerType::Work, 10),
+        entry(today(), TimerEvent::Stop, TimerType::Work, 5),
+        entry(today(), TimerEvent::Interrupt, TimerType::Work, 3),
+        entry(today(), TimerEvent::Start, TimerType::Work, 0),
+    ];
+
+    let stats = Stats::compute(&entries, today());
+
+    assert_eq!(stats.today.pomodoros, 1);
+    assert_eq!(stats.today.focus_secs, 40 * 60);
+    assert_eq!(stats.today.interruptions, 1);
+
+    assert_eq!(stats.week.pomodoros, 2);
+    assert_eq!(stats.week.focus_secs, 65 * 60);
+
+    assert_eq!(stats.month.pomodoros, 3);
+    assert_eq!(stats.month.focus_secs, 90 * 60);
+    assert_eq!(stats.month.interruptions, 1);
+}
+
+#[test]
+fn streaks_across_a_gap() {
+    let mut entries = [1, 2, 3, 4, 9, 12, 13].map(|day| finished(date(10, day)));
+    // a day with only skipped work isn't active
+    entries[4] = entry(date(10, 9), TimerEvent::Skip, TimerType::Work, 10);
+
+    // a streak that ended yesterday still goes on
+    let stats = Stats::compute(&entries, today());
+    assert_eq!(stats.current_streak, 2);
+    assert_eq!(stats.longest_streak, 4);
+
+    let stats = Stats::compute(&entries, date(10, 15));
+    assert_eq!(stats.current_streak, 0);
+    assert_eq!(stats.longest_streak, 4);
+}
+
+#[test]
+fn completion_ratio() {
+    assert_eq!(Stats::compute(&[], today()).completion_ratio(), None);
+
+    let entries = [
+        finished(date(10, 1)),
+        finished(date(10, 2)),
+        entry(date(10, 2), TimerEvent::Skip, TimerType::Work, 1),
+        // stopped timers are neither
+        entry(date(10, 3), TimerEvent::Stop, TimerType::Work, 1),
+        finished(date(10, 3)),
+    ];
+
+    let stats = Stats::compute(&entries, today());
+    assert_eq!((stats.completed, stats.skipped), (3, 1));
+    assert_eq!(stats.completion_ratio(), Some(0.75));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stopping_a_finished_timer_is_not_counted_again() {
+    let controller = Controller::new(Config::default());
+    let rx = record(&controller, &TimerEvent::ALL);
+
+    Controller::start(&controller).expect("Failed to start timer");
+    sleep(Duration::from_secs(25 * 60 + 1)).await;
+    Controller::stop(&controller);
+
+    let entries = rx.drain().collect::<Vec<_>>();
+    let stats = Stats::compute(&entries, Local::now().date_naive());
+    assert_eq!(stats.today.pomodoros, 1);
+    assert_eq!(stats.today.focus_secs, 25 * 60);
+
+    // a timer stopped before it finished still counts
+    let entries = [
+        finished(today()),
+        entry(today(), TimerEvent::Start, TimerType::Work, 0),
+        entry(today(), TimerEvent::Stop, TimerType::Work, 10),
+    ];
+    assert_eq!(Stats::compute(&entries, today()).today.focus_secs, 35 * 60);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::{minutes, record};
+use pomo_cli::config::ConfigFile;
+use pomo_cli::controller::{Config, Controller};
+use pomo_cli::suspend::{SuspendDetector, SuspendPolicy};
+use pomo_cli::timer::{TimerEvent, TimerState};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+
+// a work timer ten minutes in
+async fn running_timer(policy: SuspendPolicy) -> Arc<Mutex<Controller>> {
+    let controller = Controller::new(Config {
+        suspend: policy,
+        ..Config::default()
+    });
+    Controller::start(&controller).expect("Failed to start timer");
+
+    sleep(minutes(10)).await;
+    controller
+}
+
+#[test]
+fn detects_the_clocks_drifting_apart() {
+    let start = Instant::now();
+    let mut detector = SuspendDetector::starting_at(start, Duration::from_secs(100));
+
+    // both clocks moved on together, give or take some noise
+    assert_eq!(
+        detector.check_at(
+            start + Duration::from_secs(5),
+            Duration::from_millis(105_300)
+        ),
+        None
+    );
+
+    // the boot clock kept going during twenty minutes of suspend
+    assert_eq!(
+        detector.check_at(
+            start + Duration::from_secs(10),
+            Duration::from_millis(1_310_300)
+        ),
+        Some(minutes(20))
+    );
+
+    // the monotonic clock running ahead, e.g. in paused time, isn't a suspend
+    assert_eq!(
+        detector.check_at(start + minutes(60), Duration::from_secs(1311)),
+        None
+    );
+}
+
+#[test]
+fn policy_from_config_file() {
+    let file: ConfigFile = toml::from_str(r#"suspend = "pause""#).expect("Failed to parse config");
+    let config = file.settings.apply(Config::default());
+    assert_eq!(config.suspend, SuspendPolicy::Pause);
+
+    assert_eq!(Config::default().suspend, SuspendPolicy::Count);
+    assert!("sleep".parse::<SuspendPolicy>().is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspended_time_counts_as_elapsed() {
+    let controller = running_timer(SuspendPolicy::Count).await;
+
+    let rx = record(&controller, &[TimerEvent::Suspend, TimerEvent::Wake]);
+
+    Controller::suspended(&controller, minutes(5));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Running);
+    assert_eq!(status.remaining_secs, 10 * 60);
+
+    let entries = rx.drain().collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].event, TimerEvent::Suspend);
+    assert_eq!(entries[1].event, TimerEvent::Wake);
+    assert_eq!(entries[1].suspended_secs, Some(5 * 60));
+
+    // it keeps running towards its new end
+    sleep(minutes(10) + Duration::from_secs(1)).await;
+    assert_eq!(Controller::status(&controller).state, TimerState::Stopped);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_longer_than_the_timer_finishes_it() {
+    let controller = running_timer(SuspendPolicy::Count).await;
+
+    Controller::suspended(&controller, minutes(60));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Stopped);
+    assert_eq!(status.remaining_secs, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_pauses_the_timer() {
+    let controller = running_timer(SuspendPolicy::Pause).await;
+
+    Controller::suspended(&controller, minutes(60));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Paused);
+    assert_eq!(status.remaining_secs, 15 * 60);
+}
+
+#[tokio::test(start_paused = true)]
+async fn suspend_finishes_the_timer() {
+    let controller = running_timer(SuspendPolicy::Finish).await;
+
+    Controller::suspended(&controller, minutes(1));
+
+    let status = Controller::status(&controller);
+    assert_eq!(status.state, TimerState::Stopped);
+    assert_eq!(status.remaining_secs, 0);
+
+    // a paused timer stays as it was, without any events
+    Controller::next(&controller).expect("Failed to start next timer");
+    Controller::pause(&controller);
+    let rx = record(&controller, &[TimerEvent::Suspend, TimerEvent::Wake]);
+    Controller::suspended(&controller, minutes(1));
+    assert_eq!(Controller::status(&controller).state, TimerState::Paused);
+    assert!(rx.is_empty());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::temp_dir;
+use pomo_cli::tasks::TaskList;
+
+#[test]
+fn first_task_becomes_active() {
+    let mut task_list = TaskList::default();
+    task_list.add("write RFC", Some(3));
+    task_list.add("review PRs", None);
+
+    assert_eq!(task_list.active().map(|task| task.id), Some(1));
+}
+
+#[test]
+fn credit_task_by_id() {
+    let mut task_list = TaskList::default();
+    task_list.add("write RFC", Some(3));
+    task_list.add("write RFC", None);
+
+    // whichever task is active, and even if the titles are the same
+    task_list.credit(1);
+    task_list.credit(1);
+    task_list.credit(2);
+    assert!(task_list.credit(3).is_none());
+
+    let pomodoros: Vec<u64> = task_list
+        .tasks()
+        .iter()
+        .map(|task| task.pomodoros)
+        .collect();
+    assert_eq!(pomodoros, vec![2, 1]);
+    assert_eq!(task_list.tasks()[0].to_string(), "write RFC (2/3)");
+}
+
+#[test]
+fn complete_tasks_in_order() {
+    let mut task_list = TaskList::default();
+    task_list.add("one", None);
+    task_list.add("two", None);
+    task_list.add("three", None);
+
+    // continue after the task that was done, then wrap around to the ones that were skipped
+    assert!(task_list.activate(2));
+    assert_eq!(task_list.complete_active().map(|task| task.id), Some(3));
+    assert_eq!(task_list.complete_active().map(|task| task.id), Some(1));
+    assert_eq!(task_list.complete_active(), None);
+
+    // done tasks can't be picked again
+    assert!(!task_list.activate(2));
+    assert_eq!(task_list.active(), None);
+}
+
+#[test]
+fn save_and_load() {
+    let dir = temp_dir("tasks");
+    let path = dir.join("tasks.json");
+
+    let mut task_list = TaskList::default();
+    task_list.add("write RFC", Some(3));
+    task_list.credit(1);
+    task_list.save(&path).expect("Failed to save tasks");
+
+    let loaded = TaskList::load(&path).expect("Failed to load tasks");
+    assert_eq!(loaded.tasks(), task_list.tasks());
+    assert_eq!(loaded.active().map(|task| task.id), Some(1));
+
+    // a missing file is an empty list
+    let missing = dir.join("missing.json");
+    assert!(TaskList::load(&missing)
+        .expect("Failed to load tasks")
+        .tasks()
+        .is_empty());
+}
@@ -0,0 +1,133 @@
+// Draws the terminal UI into a test backend and presses its keys, against a daemon like the one
+// `pomo ui` talks to.
+mod common;
+
+use common::TestDaemon;
+use pomo_cli::timer::{TimerState, TimerType};
+use pomo_cli::ui::{self, Ui};
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::Terminal;
+use std::time::Duration;
+use tokio::time::sleep;
+
+// the text on the screen, one line per row
+fn screen(ui: &Ui) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(80, 14)).expect("Failed to create terminal");
+    terminal
+        .draw(|frame| ui.draw(frame))
+        .expect("Failed to draw");
+
+    let buffer = terminal.backend().buffer();
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            text.push_str(buffer[(x, y)].symbol());
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn press(ui: &mut Ui, code: KeyCode) {
+    ui.key(code).expect("Failed to send key");
+    ui.refresh().expect("Failed to refresh");
+}
+
+#[tokio::test(start_paused = true)]
+async fn shows_the_timer() {
+    let daemon = TestDaemon::serve("ui-show");
+    daemon.start("review");
+
+    sleep(Duration::from_secs(60)).await;
+
+    let ui = Ui::new(daemon.client(Some("review")), Some("review".to_string()))
+        .expect("Failed to open UI");
+    let screen = screen(&ui);
+
+    assert!(screen.contains(" pomo · review "), "{}", screen);
+    assert!(screen.contains("Work  running"), "{}", screen);
+    assert!(screen.contains("24:00 / 25:00"), "{}", screen);
+    assert!(
+        screen.contains("Pomodoro 1 of 4 until the long break"),
+        "{}",
+        screen
+    );
+    assert!(screen.contains("Next: break for 05:00"), "{}", screen);
+    assert!(screen.contains("q quit"), "{}", screen);
+}
+
+#[tokio::test(start_paused = true)]
+async fn keys_control_the_timer() {
+    let daemon = TestDaemon::serve("ui-keys");
+    daemon.start("review");
+
+    let mut ui = Ui::new(daemon.client(Some("review")), Some("review".to_string()))
+        .expect("Failed to open UI");
+
+    press(&mut ui, KeyCode::Char(' '));
+    assert_eq!(
+        daemon.status(Some("review")).unwrap().state,
+        TimerState::Paused
+    );
+    press(&mut ui, KeyCode::Char(' '));
+    assert_eq!(
+        daemon.status(Some("review")).unwrap().state,
+        TimerState::Running
+    );
+
+    press(&mut ui, KeyCode::Char('e'));
+    assert!(screen(&ui).contains("30:00 / 30:00"), "{}", screen(&ui));
+
+    press(&mut ui, KeyCode::Char('i'));
+    assert!(screen(&ui).contains("Interruptions: 1 internal, 0 external"));
+
+    press(&mut ui, KeyCode::Char('n'));
+    let status = daemon.status(Some("review")).unwrap();
+    assert_eq!(status.timer_type, TimerType::Break);
+    assert!(screen(&ui).contains("Break  running"), "{}", screen(&ui));
+
+    // breaks can't be interrupted, the daemon's reason is shown instead of the keys
+    press(&mut ui, KeyCode::Char('i'));
+    let screen = screen(&ui);
+    assert!(
+        screen.contains("Only a work timer that hasn't finished can be interrupted."),
+        "{}",
+        screen
+    );
+    assert!(!screen.contains("q quit"), "{}", screen);
+    assert_eq!(
+        daemon
+            .status(Some("review"))
+            .unwrap()
+            .interruptions
+            .internal,
+        0
+    );
+
+    // unknown keys do nothing
+    press(&mut ui, KeyCode::Char('x'));
+
+    // stopping the only timer shuts pomo down, which ends the UI
+    ui.key(KeyCode::Char('s')).expect("Failed to stop timer");
+    let error = ui.refresh().expect_err("Expected the timer to be gone");
+    assert!(ui::timer_stopped(&error), "{:?}", error);
+}
+
+#[tokio::test(start_paused = true)]
+async fn space_starts_the_next_timer_when_finished() {
+    let daemon = TestDaemon::serve("ui-finished");
+    daemon.start("review");
+
+    let mut ui = Ui::new(daemon.client(Some("review")), Some("review".to_string()))
+        .expect("Failed to open UI");
+
+    sleep(Duration::from_secs(25 * 60 + 1)).await;
+    ui.refresh().expect("Failed to refresh");
+    assert!(screen(&ui).contains("00:00 / 25:00"), "{}", screen(&ui));
+
+    press(&mut ui, KeyCode::Char(' '));
+    let status = daemon.status(Some("review")).unwrap();
+    assert_eq!(status.timer_type, TimerType::Break);
+    assert_eq!(status.state, TimerState::Running);
+}